@@ -0,0 +1,2985 @@
+use std::{collections::{HashMap, VecDeque}, fmt, fs, io::Write, time::{self, SystemTime, UNIX_EPOCH}};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::{json::{self, Json}, roll::{DiceNum, Roll}, score::{Rules, Ruleset, ScoreTable, ScoreType, UPPER_BONUS_THRESHOLD, completion_probability, estimate_bonus_odds, evaluate_score, qualifies, scoring_panel, verify_cases, verify_scoring}, persist::{SAVE_DIR, slot_path, write_atomically}, ui::{commands_panel, leaderboard_panel, saves_panel}};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum HintLevel {
+    Full,
+    Brief,
+    Off,
+}
+
+impl HintLevel {
+    fn name(&self) -> &'static str {
+        match self {
+            HintLevel::Full => "full",
+            HintLevel::Brief => "brief",
+            HintLevel::Off => "off",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "full" => Some(HintLevel::Full),
+            "brief" => Some(HintLevel::Brief),
+            "off" => Some(HintLevel::Off),
+            _ => None,
+        }
+    }
+}
+
+// finished games this session, used to judge how much help the player still wants
+#[derive(Clone, PartialEq)]
+pub struct SessionStats {
+    pub games_played: u32,
+    pub total_points: u32,
+}
+
+impl SessionStats {
+    fn average_score(&self) -> Option<u32> {
+        self.total_points.checked_div(self.games_played)
+    }
+
+    // hints thin out once the player's average passes these totals
+    fn hint_level(&self) -> HintLevel {
+        match self.average_score() {
+            Some(average) if average >= 200 => HintLevel::Off,
+            Some(average) if average >= 150 => HintLevel::Brief,
+            _ => HintLevel::Full,
+        }
+    }
+}
+
+// moments worth ringing the terminal bell for
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Notice {
+    Yacht,
+    UpperBonus,
+    GameOver,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum GameStates {
+    Rolling,
+    GameOver,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Game {
+    pub game_state: GameStates,
+    pub rolls_used: u8,
+    pub rolls_per_turn: u8,
+    seed: u64,
+    rng: ChaCha12Rng,
+    pub current_roll: Roll,
+    pub players: Vec<Player>,
+    pub current_player: usize,
+    pub lucky_face: Option<u8>,
+    debug: bool,
+    pub session: SessionStats,
+    pub hint_override: Option<HintLevel>,
+    pub coach: bool,
+    pub confirm_zero: bool,
+    // scoring less than another open category would waits for a 'yes' too
+    pub confirm_better: bool,
+    // a score of 0, or one worth less than it could be, waiting on the player's 'yes'
+    pending_score: Option<ScoreType>,
+    pub turn_limit: Option<time::Duration>,
+    turn_started: time::Instant,
+    pub rules: Rules,
+    // every face rolled this game, indexed by face so index 0 is unused
+    faces_rolled: Vec<u32>,
+    stats: Stats,
+    // the date of a daily challenge, whose seed comes from it
+    daily: Option<String>,
+    // bonus odds by upper total and open upper faces, the model only depends on those
+    bonus_odds_cache: HashMap<(u16, Vec<u8>), f64>,
+    pub drill: Option<Drill>,
+    match_play: Option<MatchPlay>,
+    transition_log: Option<TransitionLog>,
+    pub msg: String,
+    // the message is a refusal or a failure, drawn in the theme's error colour
+    pub msg_error: bool,
+    pub panel: Vec<String>,
+    // the next line typed is a name for the leaderboard rather than a command
+    pub awaiting_name: bool,
+    // a game with several players just ended, main puts everyone's total on the leaderboard
+    pub record_pending: bool,
+    // category whose dice are picked out on the next draw
+    pub highlight: Option<ScoreType>,
+    // whether the next draw shows what the open categories would score
+    pub preview: bool,
+    // what happened since the last draw that's worth a bell
+    pub notices: Vec<Notice>,
+    // the most recent state last, taken before each move so it can be undone
+    history: VecDeque<Snapshot>,
+    // the game as it stood when it was started or loaded, and every move typed since, for 'record'
+    recording_start: Json,
+    recording: Vec<String>,
+}
+
+// everything a move can change, including the rng so undoing a roll can't be used to roll again for better dice
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    game_state: GameStates,
+    rolls_used: u8,
+    rng: ChaCha12Rng,
+    current_roll: Roll,
+    players: Vec<Player>,
+    current_player: usize,
+    lucky_face: Option<u8>,
+    faces_rolled: Vec<u32>,
+    stats: Stats,
+    drill: Option<Drill>,
+    match_play: Option<MatchPlay>,
+    seed: u64,
+    daily: Option<String>,
+}
+
+// how many moves back undo can go
+const UNDO_LIMIT: usize = 5;
+
+#[derive(Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    pub score_table: ScoreTable,
+    pub lucky_bonus: u16,
+    rolls_taken: u32,
+    // 1 for the first player to fill their card, 2 for the next and so on
+    finished_at: Option<u32>,
+    // played by ai_choose rather than typed commands
+    pub is_ai: bool,
+}
+
+impl Player {
+    fn new(name: &str) -> Self {
+        Player {
+            name: name.to_string(),
+            score_table: ScoreTable::new(),
+            lucky_bonus: 0,
+            rolls_taken: 0,
+            finished_at: None,
+            is_ai: false,
+        }
+    }
+
+    fn computer() -> Self {
+        Player { is_ai: true, ..Player::new("Computer") }
+    }
+
+    fn reset(&mut self) {
+        self.score_table.reset_scores();
+        self.lucky_bonus = 0;
+        self.rolls_taken = 0;
+        self.finished_at = None;
+    }
+
+    pub fn total(&self) -> u16 {
+        self.score_table.table_total() + self.lucky_bonus
+    }
+
+    fn summary(&self) -> PlayerSummary {
+        PlayerSummary {
+            name: self.name.clone(),
+            total: self.total(),
+            upper_bonus: self.score_table.upper_total() >= UPPER_BONUS_THRESHOLD,
+            rolls_taken: self.rolls_taken,
+            finished_at: self.finished_at.unwrap_or(u32::MAX),
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        let scores = ScoreType::all()
+            .iter()
+            .filter_map(|score_type| self.score_table.table.get(score_type).map(|&score| (score_type.key().to_string(), Json::number(score))))
+            .collect();
+        let turn_filled = ScoreType::all()
+            .iter()
+            .filter_map(|score_type| self.score_table.turn_filled.get(score_type).map(|&turn| (score_type.key().to_string(), Json::number(turn))))
+            .collect();
+
+        Json::Object(vec![
+            ("name".to_string(), Json::Str(self.name.clone())),
+            ("scores".to_string(), Json::Object(scores)),
+            ("turn_filled".to_string(), Json::Object(turn_filled)),
+            ("lucky_bonus".to_string(), Json::number(self.lucky_bonus)),
+            ("yacht_bonus".to_string(), Json::number(self.score_table.yacht_bonus)),
+            ("rolls_taken".to_string(), Json::number(self.rolls_taken)),
+            ("finished_at".to_string(), self.finished_at.map_or(Json::Null, Json::number)),
+            ("is_ai".to_string(), Json::Bool(self.is_ai)),
+        ])
+    }
+
+    // single player saves from before multiplayer kept these fields at the top level, so anything
+    // added since then is optional
+    fn from_json(json: &Json) -> Result<Self, String> {
+        let field = |key: &str| json.get(key).ok_or(format!("Save is missing '{}'", key));
+
+        let name = match json.get("name") {
+            Some(name) => name.as_str().ok_or("Save has an invalid player name")?,
+            None => "Player",
+        };
+        let mut player = Player::new(name);
+
+        for (key, value) in field("scores")?.as_object().ok_or("Save has invalid 'scores'")? {
+            let score_type = ScoreType::from_key(key).ok_or(format!("Save has unknown category '{}'", key))?;
+            let score = value.as_u64().and_then(|num| u8::try_from(num).ok()).ok_or(format!("Save has an invalid score for '{}'", key))?;
+            player.score_table.table.insert(score_type, score);
+        }
+
+        if let Some(turn_filled) = json.get("turn_filled") {
+            for (key, value) in turn_filled.as_object().ok_or("Save has invalid 'turn_filled'")? {
+                let score_type = ScoreType::from_key(key).ok_or(format!("Save has unknown category '{}'", key))?;
+                let turn = value.as_u64().and_then(|num| u8::try_from(num).ok()).ok_or(format!("Save has an invalid turn for '{}'", key))?;
+                player.score_table.turn_filled.insert(score_type, turn);
+            }
+        }
+
+        player.lucky_bonus = field("lucky_bonus")?
+            .as_u64()
+            .and_then(|num| u16::try_from(num).ok())
+            .ok_or("Save has an invalid 'lucky_bonus'")?;
+        if let Some(yacht_bonus) = json.get("yacht_bonus") {
+            player.score_table.yacht_bonus = yacht_bonus.as_u64().and_then(|num| u16::try_from(num).ok()).ok_or("Save has an invalid 'yacht_bonus'")?;
+        }
+        if let Some(rolls_taken) = json.get("rolls_taken") {
+            player.rolls_taken = rolls_taken.as_u64().and_then(|num| u32::try_from(num).ok()).ok_or("Save has an invalid 'rolls_taken'")?;
+        }
+        player.finished_at = match json.get("finished_at") {
+            None | Some(Json::Null) => None,
+            Some(order) => Some(order.as_u64().and_then(|num| u32::try_from(num).ok()).ok_or("Save has an invalid 'finished_at'")?),
+        };
+        if let Some(is_ai) = json.get("is_ai") {
+            player.is_ai = is_ai.as_bool().ok_or("Save has an invalid 'is_ai'")?;
+        }
+
+        Ok(player)
+    }
+}
+
+// what the final standings are decided on
+#[derive(Debug, PartialEq, Clone)]
+struct PlayerSummary {
+    name: String,
+    total: u16,
+    upper_bonus: bool,
+    rolls_taken: u32,
+    finished_at: u32,
+}
+
+// highest total wins, ties go to whoever reached the upper bonus, then whoever needed fewer rolls,
+// then whoever filled their card first
+fn compare_summaries(a: &PlayerSummary, b: &PlayerSummary) -> std::cmp::Ordering {
+    b.total
+        .cmp(&a.total)
+        .then(b.upper_bonus.cmp(&a.upper_bonus))
+        .then(a.rolls_taken.cmp(&b.rolls_taken))
+        .then(a.finished_at.cmp(&b.finished_at))
+}
+
+fn rank_players(players: &[Player]) -> Vec<PlayerSummary> {
+    let mut summaries: Vec<PlayerSummary> = players.iter().map(Player::summary).collect();
+    summaries.sort_by(compare_summaries);
+    summaries
+}
+
+fn standings_panel(ranked: &[PlayerSummary]) -> Vec<String> {
+    let mut lines = vec!["FINAL STANDINGS".to_string()];
+
+    for (i, summary) in ranked.iter().enumerate() {
+        let place = match i {
+            0 => "🥇".to_string(),
+            1 => "🥈".to_string(),
+            2 => "🥉".to_string(),
+            _ => format!("{}.", i + 1),
+        };
+        let bonus = if summary.upper_bonus { "bonus, " } else { "" };
+        lines.push(format!("{:<3} {:<16}{:>4}  ({}{} rolls)", place, summary.name, summary.total, bonus, summary.rolls_taken));
+    }
+
+    lines
+}
+
+// where a headless run left off, with a card for every player in table order
+pub struct GameSummary {
+    pub seed: u64,
+    pub game_over: bool,
+    pub msg: String,
+    pub cards: Vec<PlayerCard>,
+}
+
+pub struct PlayerCard {
+    pub name: String,
+    // None for a category still open
+    pub scores: Vec<(ScoreType, Option<u8>)>,
+    pub total: u16,
+}
+
+impl GameSummary {
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = vec![format!("Seed: {}", self.seed)];
+
+        for card in &self.cards {
+            lines.push(format!("{}:", card.name));
+            for (score_type, score) in &card.scores {
+                let score = score.map_or("-".to_string(), |score| score.to_string());
+                lines.push(format!("  {:<16}{:>3}", score_type.name(), score));
+            }
+            lines.push(format!("  {:<16}{:>3}", "Total", card.total));
+        }
+
+        lines.push(if self.game_over { "Game over".to_string() } else { "Game not finished".to_string() });
+        lines.push(self.msg.clone());
+        lines
+    }
+}
+
+const DEBUG_LOG_PATH: &str = "yacht_debug.log";
+
+// appends every game state change to a file under --debug, for chasing state machine bugs
+#[derive(Clone, PartialEq)]
+struct TransitionLog {
+    path: String,
+}
+
+impl TransitionLog {
+    fn record(&self, from: &str, to: &str, trigger: &Command) {
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_millis());
+        let line = format!("{} {} -> {} on {:?}\n", millis, from, to, trigger);
+
+        // a debug aid shouldn't take the game down, so failed writes are dropped
+        if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(&self.path) {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+// practice stats for repeatedly going after a single category
+#[derive(Clone, PartialEq)]
+pub struct Drill {
+    target: ScoreType,
+    attempts: u32,
+    successes: u32,
+    rolls_on_success: u32,
+}
+
+impl Drill {
+    fn new(target: ScoreType) -> Self {
+        Drill { target, attempts: 0, successes: 0, rolls_on_success: 0 }
+    }
+
+    fn record(&mut self, hit: bool, rolls_used: u8) {
+        self.attempts += 1;
+        if hit {
+            self.successes += 1;
+            self.rolls_on_success += rolls_used as u32;
+        }
+    }
+
+    fn summary(&self) -> String {
+        if self.successes == 0 {
+            return format!("0 hits in {} attempts", self.attempts);
+        }
+
+        let successes = self.successes as f32;
+        format!(
+            "{} hits in {} attempts ({:.0}%), {:.1} attempts and {:.1} rolls per hit",
+            self.successes,
+            self.attempts,
+            successes * 100.0 / self.attempts as f32,
+            self.attempts as f32 / successes,
+            self.rolls_on_success as f32 / successes,
+        )
+    }
+}
+
+// games played one after another until someone's running total reaches the target
+#[derive(Clone, PartialEq)]
+struct MatchPlay {
+    target: u32,
+    games: u32,
+    // by player, in the order they play
+    totals: Vec<u32>,
+    wins: Vec<u32>,
+}
+
+impl MatchPlay {
+    fn new(target: u32, players: usize) -> Self {
+        MatchPlay { target, games: 0, totals: vec![0; players], wins: vec![0; players] }
+    }
+
+    // a finished game's totals are added on, and whoever scored most takes the game, everyone tied for it on a draw
+    fn record(&mut self, totals: &[u16]) {
+        self.games += 1;
+        let best = totals.iter().copied().max().unwrap_or(0);
+        for (i, &total) in totals.iter().enumerate() {
+            self.totals[i] += total as u32;
+            if total == best {
+                self.wins[i] += 1;
+            }
+        }
+    }
+
+    // once anyone has reached the target, the highest running total, then the most games won
+    fn winner(&self) -> Option<usize> {
+        if self.totals.iter().all(|&total| total < self.target) {
+            return None;
+        }
+        (0..self.totals.len()).max_by_key(|&i| (self.totals[i], self.wins[i]))
+    }
+
+    // games won only mean something with someone to win them against
+    fn standing(&self, names: &[&str]) -> String {
+        let players: Vec<String> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| if names.len() > 1 { format!("{} {} ({} won)", name, self.totals[i], self.wins[i]) } else { format!("{} {}", name, self.totals[i]) })
+            .collect();
+        format!("{} of {}", players.join(", "), self.target)
+    }
+
+    fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("target".to_string(), Json::number(self.target)),
+            ("games".to_string(), Json::number(self.games)),
+            ("totals".to_string(), Json::Array(self.totals.iter().map(|&total| Json::number(total)).collect())),
+            ("wins".to_string(), Json::Array(self.wins.iter().map(|&wins| Json::number(wins)).collect())),
+        ])
+    }
+
+    fn from_json(json: &Json, players: usize) -> Result<Self, String> {
+        let count = |key: &str| {
+            json.get(key)
+                .and_then(Json::as_u64)
+                .and_then(|num| u32::try_from(num).ok())
+                .ok_or(format!("Save has an invalid match '{}'", key))
+        };
+        let per_player = |key: &str| {
+            json.get(key)
+                .and_then(Json::as_array)
+                .filter(|saved| saved.len() == players)
+                .and_then(|saved| saved.iter().map(|num| num.as_u64().and_then(|num| u32::try_from(num).ok())).collect::<Option<Vec<u32>>>())
+                .ok_or(format!("Save has an invalid match '{}'", key))
+        };
+
+        Ok(MatchPlay { target: count("target")?, games: count("games")?, totals: per_player("totals")?, wins: per_player("wins")? })
+    }
+}
+
+// running counts for the stats command and the game over summary
+#[derive(Clone, Default, PartialEq)]
+struct Stats {
+    rolls: u32,
+    dice_rolled: u32,
+    // points scored in each category by every player this game and how many times it was scored
+    category_totals: HashMap<ScoreType, (u32, u32)>,
+    zero_turns: u32,
+}
+
+impl Stats {
+    fn record_roll(&mut self, dice: usize) {
+        self.rolls += 1;
+        self.dice_rolled += dice as u32;
+    }
+
+    fn record_score(&mut self, score_type: ScoreType, score: u8) {
+        let (total, count) = self.category_totals.entry(score_type).or_insert((0, 0));
+        *total += score as u32;
+        *count += 1;
+        if score == 0 {
+            self.zero_turns += 1;
+        }
+    }
+
+    fn average(&self, score_type: &ScoreType) -> Option<f32> {
+        self.category_totals.get(score_type).map(|&(total, count)| total as f32 / count as f32)
+    }
+
+    fn summary(&self) -> String {
+        format!("{} rolls, {} dice, {} turn{} scored 0", self.rolls, self.dice_rolled, self.zero_turns, if self.zero_turns == 1 { "" } else { "s" })
+    }
+
+    fn panel(&self, rules: &Rules) -> Vec<String> {
+        let entries: Vec<String> = rules
+            .categories()
+            .iter()
+            .map(|score_type| match self.average(score_type) {
+                Some(average) => format!("{:<16}{:>5.1}", score_type.name(), average),
+                None => format!("{:<16}{:>5}", score_type.name(), "-"),
+            })
+            .collect();
+
+        let mut lines = vec![format!("GAME STATS   {}", self.summary())];
+        let rows = entries.len().div_ceil(2);
+        for row in 0..rows {
+            let mut line = String::new();
+            for entry in entries.iter().skip(row).step_by(rows) {
+                line.push_str(&format!("{:<28}", entry));
+            }
+            lines.push(line.trim_end().to_string());
+        }
+
+        lines
+    }
+
+    fn to_json(&self) -> Json {
+        let categories = ScoreType::all()
+            .iter()
+            .filter_map(|score_type| {
+                self.category_totals
+                    .get(score_type)
+                    .map(|&(total, count)| (score_type.key().to_string(), Json::Array(vec![Json::number(total), Json::number(count)])))
+            })
+            .collect();
+
+        Json::Object(vec![
+            ("rolls".to_string(), Json::number(self.rolls)),
+            ("dice_rolled".to_string(), Json::number(self.dice_rolled)),
+            ("zero_turns".to_string(), Json::number(self.zero_turns)),
+            ("categories".to_string(), Json::Object(categories)),
+        ])
+    }
+
+    fn from_json(json: &Json) -> Result<Self, String> {
+        let count = |key: &str| {
+            json.get(key)
+                .and_then(Json::as_u64)
+                .and_then(|num| u32::try_from(num).ok())
+                .ok_or(format!("Save has an invalid stats '{}'", key))
+        };
+
+        let mut stats = Stats { rolls: count("rolls")?, dice_rolled: count("dice_rolled")?, zero_turns: count("zero_turns")?, ..Stats::default() };
+        for (key, value) in json.get("categories").and_then(Json::as_object).ok_or("Save has invalid stats 'categories'")? {
+            let score_type = ScoreType::from_key(key).ok_or(format!("Save has unknown category '{}'", key))?;
+            let pair: Vec<u32> = value
+                .as_array()
+                .filter(|pair| pair.len() == 2)
+                .and_then(|pair| pair.iter().map(|num| num.as_u64().and_then(|num| u32::try_from(num).ok())).collect())
+                .ok_or(format!("Save has invalid stats for '{}'", key))?;
+            stats.category_totals.insert(score_type, (pair[0], pair[1]));
+        }
+
+        Ok(stats)
+    }
+}
+
+// what a game is set up with from the command line, the rest of the options are for the terminal
+pub struct GameOptions {
+    pub rolls_per_turn: u8,
+    pub lucky: bool,
+    pub seed: Option<u64>,
+    pub debug: bool,
+    pub drill: Option<ScoreType>,
+    pub players: Vec<String>,
+    pub rules: Rules,
+    pub coach: bool,
+    // adds a computer player after the named ones
+    pub ai: bool,
+    // scoring 0 in a category waits for a 'yes'
+    pub confirm_zero: bool,
+    // so does scoring less than another open category would
+    pub confirm_better: bool,
+    // seconds each turn may take before its cheapest open category is scratched
+    pub turn_limit: Option<u64>,
+    // games are played on until someone's running total reaches this
+    pub match_target: Option<u32>,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        GameOptions {
+            rolls_per_turn: 3,
+            lucky: false,
+            seed: None,
+            debug: false,
+            drill: None,
+            players: vec!["Player".to_string()],
+            rules: Rules::default(),
+            coach: false,
+            ai: false,
+            confirm_zero: false,
+            confirm_better: false,
+            turn_limit: None,
+            match_target: None,
+        }
+    }
+}
+
+// points added to any category scored while the lucky face shows at least three times
+const LUCKY_BONUS: u16 = 5;
+
+impl Game {
+    pub fn new(options: &GameOptions) -> Self {
+        let seed = options.seed.unwrap_or_else(rand::random);
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        let current_roll = Roll::new(&mut rng, &options.rules);
+
+        let mut game = Game {
+            game_state: GameStates::Rolling,
+            rolls_used: 1,
+            rolls_per_turn: options.rolls_per_turn,
+            seed,
+            faces_rolled: current_roll.face_counts().into_iter().map(u32::from).collect(),
+            stats: Stats { rolls: 1, dice_rolled: options.rules.dice as u32, ..Stats::default() },
+            daily: None,
+            current_roll,
+            players: options
+                .players
+                .iter()
+                .map(|name| Player::new(name))
+                .chain(options.ai.then(Player::computer))
+                .collect(),
+            current_player: 0,
+            lucky_face: if options.lucky { Some(rng.gen_range(1..=options.rules.sides)) } else { None },
+            rng,
+            debug: options.debug,
+            session: SessionStats { games_played: 0, total_points: 0 },
+            hint_override: None,
+            coach: options.coach,
+            confirm_zero: options.confirm_zero,
+            confirm_better: options.confirm_better,
+            pending_score: None,
+            turn_limit: options.turn_limit.map(time::Duration::from_secs),
+            turn_started: time::Instant::now(),
+            rules: options.rules,
+            bonus_odds_cache: HashMap::new(),
+            drill: options.drill.map(Drill::new),
+            match_play: None,
+            transition_log: if options.debug { Some(TransitionLog { path: DEBUG_LOG_PATH.to_string() }) } else { None },
+            msg: "".to_string(),
+            msg_error: false,
+            panel: Vec::new(),
+            awaiting_name: false,
+            record_pending: false,
+            highlight: None,
+            preview: false,
+            notices: Vec::new(),
+            history: VecDeque::new(),
+            recording_start: Json::Null,
+            recording: Vec::new(),
+        };
+        game.match_play = options.match_target.map(|target| MatchPlay::new(target, game.players.len()));
+        game.start_recording();
+        game
+    }
+
+    fn advance_gamestate(&mut self, trigger: &Command) {
+        let from = self.state_label();
+        match self.game_state {
+            GameStates::Rolling if self.rolls_used < self.rolls_per_turn => self.rolls_used += 1,
+            GameStates::Rolling => panic!("Cant advance from the final roll without score"),
+            GameStates::GameOver => panic!("This should probably start a new game"),
+        }
+        self.log_transition(&from, trigger);
+    }
+
+    fn state_label(&self) -> String {
+        match self.game_state {
+            GameStates::Rolling => format!("Rolling({}/{})", self.rolls_used, self.rolls_per_turn),
+            GameStates::GameOver => "GameOver".to_string(),
+        }
+    }
+
+    fn log_transition(&self, from: &str, trigger: &Command) {
+        if let Some(log) = &self.transition_log {
+            log.record(from, &self.state_label(), trigger);
+        }
+    }
+
+    pub fn is_final_roll(&self) -> bool {
+        self.rolls_used >= self.rolls_per_turn
+    }
+
+    // every turn starts on its first roll, so any roll past that was asked for
+    fn has_rolled(&self) -> bool {
+        self.rolls_used > 1
+    }
+
+    // with roll_first on, a turn can't be scored until the player has rolled, unless they never can or their time's up
+    fn must_roll_first(&self) -> bool {
+        self.rules.roll_first && !self.has_rolled() && !self.is_final_roll() && !self.out_of_time()
+    }
+
+    fn is_lucky_roll(&self) -> bool {
+        match self.lucky_face {
+            Some(face) => self.current_roll.count_face(face) >= 3,
+            None => false,
+        }
+    }
+
+    fn bonus_odds(&mut self) -> f64 {
+        let score_table = &self.players[self.current_player].score_table;
+        let key = (score_table.upper_total(), score_table.open_upper_faces());
+        let rolls_per_turn = self.rolls_per_turn;
+        let rules = self.rules;
+
+        *self.bonus_odds_cache
+            .entry(key)
+            .or_insert_with_key(|(upper_total, open_faces)| estimate_bonus_odds(*upper_total, open_faces, rolls_per_turn, &rules))
+    }
+
+    // the open upper face with the most still to make up. The bonus needs three of each face, so that's three
+    // times the face less what the dice already show of it. Faces showing at least once come first so there's
+    // something to hold, and ties go to the higher face
+    fn bonus_target(&self) -> Option<u8> {
+        let counts = self.current_roll.face_counts();
+
+        self.player()
+            .score_table
+            .open_upper_faces()
+            .into_iter()
+            .max_by_key(|&face| {
+                let showing = counts[face as usize] as i16;
+                let need = 3 * face as i16 - showing * face as i16;
+                (showing > 0, need, face)
+            })
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("game_over".to_string(), Json::Bool(self.game_state == GameStates::GameOver)),
+            ("rolls_used".to_string(), Json::number(self.rolls_used)),
+            ("rolls_per_turn".to_string(), Json::number(self.rolls_per_turn)),
+            ("seed".to_string(), Json::number(self.seed)),
+            ("rng_word_pos".to_string(), Json::number(self.rng.get_word_pos())),
+            ("dice".to_string(), Json::Array(self.current_roll.dice.iter().map(|&x| Json::number(x)).collect())),
+            ("holds".to_string(), Json::Array(self.current_roll.holds.iter().map(|&x| Json::Bool(x)).collect())),
+            ("players".to_string(), Json::Array(self.players.iter().map(Player::to_json).collect())),
+            ("current_player".to_string(), Json::number(self.current_player)),
+            ("lucky_face".to_string(), self.lucky_face.map_or(Json::Null, Json::number)),
+            ("faces_rolled".to_string(), Json::Array(self.faces_rolled[1..].iter().map(|&x| Json::number(x)).collect())),
+            ("stats".to_string(), self.stats.to_json()),
+            ("daily".to_string(), self.daily.clone().map_or(Json::Null, Json::Str)),
+            ("ruleset".to_string(), Json::Str(self.rules.ruleset.name().to_string())),
+            ("sides".to_string(), Json::number(self.rules.sides)),
+            ("match".to_string(), self.match_play.as_ref().map_or(Json::Null, MatchPlay::to_json)),
+        ])
+    }
+
+    fn from_json(json: &Json, options: &GameOptions) -> Result<Self, String> {
+        fn field<'a>(json: &'a Json, key: &str) -> Result<&'a Json, String> {
+            json.get(key).ok_or(format!("Save is missing '{}'", key))
+        }
+
+        fn small_number(json: &Json, key: &str) -> Result<u8, String> {
+            field(json, key)?
+                .as_u64()
+                .and_then(|num| u8::try_from(num).ok())
+                .ok_or(format!("Save has an invalid '{}'", key))
+        }
+
+        // a card only makes sense under the rules it was played with, and saves from before rulesets were all yacht
+        let ruleset = match json.get("ruleset") {
+            Some(name) => name.as_str().and_then(Ruleset::from_name).ok_or("Save has an unknown 'ruleset'")?,
+            None => Ruleset::Yacht,
+        };
+        if ruleset != options.rules.ruleset {
+            return Err(format!("That game was played with {} rules, start with --ruleset {} to load it", ruleset.name(), ruleset.name()));
+        }
+
+        let game_over = field(json, "game_over")?.as_bool().ok_or("Save has an invalid 'game_over'")?;
+        let rolls_used = small_number(json, "rolls_used")?;
+        let rolls_per_turn = small_number(json, "rolls_per_turn")?;
+        if rolls_per_turn == 0 || rolls_used == 0 || rolls_used > rolls_per_turn {
+            return Err("Save has an impossible roll count".to_string());
+        }
+
+        let seed = field(json, "seed")?.as_u64().ok_or("Save has an invalid 'seed'")?;
+        let word_pos = field(json, "rng_word_pos")?.as_u128().ok_or("Save has an invalid 'rng_word_pos'")?;
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+        rng.set_word_pos(word_pos);
+
+        // likewise the dice, saves from before --dice and --sides were always 5d6
+        let sides = match json.get("sides") {
+            Some(_) => small_number(json, "sides")?,
+            None => 6,
+        };
+        if sides != options.rules.sides {
+            return Err(format!("That game was played with {}-sided dice, start with --sides {} to load it", sides, sides));
+        }
+        let saved_dice = field(json, "dice")?.as_array().ok_or("Save has invalid 'dice'")?;
+        if saved_dice.len() != options.rules.dice as usize {
+            return Err(format!("That game was played with {} dice, start with --dice {} to load it", saved_dice.len(), saved_dice.len()));
+        }
+        let dice = saved_dice
+            .iter()
+            .map(|saved| saved.as_u64().and_then(|x| u8::try_from(x).ok()).ok_or("Save has an impossible die".to_string()))
+            .collect::<Result<Vec<u8>, String>>()?;
+        let mut current_roll = Roll::try_from_dice(dice, sides).map_err(|err| format!("Save has an impossible die, {}", err))?;
+
+        let saved_holds = field(json, "holds")?.as_array().ok_or("Save has invalid 'holds'")?;
+        if saved_holds.len() != current_roll.dice.len() {
+            return Err("Save has the wrong number of holds".to_string());
+        }
+        current_roll.holds = saved_holds
+            .iter()
+            .map(|saved| saved.as_bool().ok_or("Save has an invalid hold".to_string()))
+            .collect::<Result<Vec<bool>, String>>()?;
+
+        let (players, current_player) = match json.get("players") {
+            Some(players) => {
+                let players = players
+                    .as_array()
+                    .filter(|players| !players.is_empty())
+                    .ok_or("Save has invalid 'players'")?
+                    .iter()
+                    .map(Player::from_json)
+                    .collect::<Result<Vec<Player>, String>>()?;
+                let current_player = field(json, "current_player")?
+                    .as_u64()
+                    .map(|num| num as usize)
+                    .filter(|&num| num < players.len())
+                    .ok_or("Save has an invalid 'current_player'")?;
+                (players, current_player)
+            },
+            None => (vec![Player::from_json(json)?], 0),
+        };
+
+        let lucky_face = match field(json, "lucky_face")? {
+            Json::Null => None,
+            face => Some(face.as_u64().filter(|x| (1..=sides as u64).contains(x)).ok_or("Save has an invalid 'lucky_face'")? as u8),
+        };
+
+        // older saves didn't count rolled faces, those games just start counting from here
+        let mut faces_rolled = vec![0; sides as usize + 1];
+        if let Some(saved) = json.get("faces_rolled") {
+            let saved = saved.as_array().filter(|saved| saved.len() == sides as usize).ok_or("Save has invalid 'faces_rolled'")?;
+            for (count, saved) in faces_rolled[1..].iter_mut().zip(saved) {
+                *count = saved.as_u64().and_then(|num| u32::try_from(num).ok()).ok_or("Save has an invalid face count")?;
+            }
+        }
+
+        let daily = match json.get("daily") {
+            None | Some(Json::Null) => None,
+            Some(date) => Some(date.as_str().ok_or("Save has an invalid 'daily'")?.to_string()),
+        };
+
+        // the same goes for stats
+        let stats = match json.get("stats") {
+            Some(stats) => Stats::from_json(stats)?,
+            None => Stats::default(),
+        };
+
+        let match_play = match json.get("match") {
+            None | Some(Json::Null) => None,
+            Some(saved) => Some(MatchPlay::from_json(saved, players.len())?),
+        };
+
+        Ok(Game {
+            game_state: if game_over { GameStates::GameOver } else { GameStates::Rolling },
+            rolls_used,
+            rolls_per_turn,
+            seed,
+            rng,
+            current_roll,
+            faces_rolled,
+            stats,
+            daily,
+            players,
+            current_player,
+            lucky_face,
+            debug: options.debug,
+            session: SessionStats { games_played: 0, total_points: 0 },
+            hint_override: None,
+            coach: options.coach,
+            confirm_zero: options.confirm_zero,
+            confirm_better: options.confirm_better,
+            pending_score: None,
+            turn_limit: options.turn_limit.map(time::Duration::from_secs),
+            turn_started: time::Instant::now(),
+            rules: options.rules,
+            bonus_odds_cache: HashMap::new(),
+            drill: None,
+            match_play,
+            transition_log: if options.debug { Some(TransitionLog { path: DEBUG_LOG_PATH.to_string() }) } else { None },
+            msg: "".to_string(),
+            msg_error: false,
+            panel: Vec::new(),
+            awaiting_name: false,
+            record_pending: false,
+            highlight: None,
+            preview: false,
+            notices: Vec::new(),
+            history: VecDeque::new(),
+            recording_start: Json::Null,
+            recording: Vec::new(),
+        })
+    }
+
+    pub fn load(path: &str, options: &GameOptions) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+        let json = json::parse(&contents).map_err(|e| format!("{} is not a valid save: {}", path, e))?;
+        Game::from_json(&json, options)
+    }
+
+    // a named save in dir, with the note to list it by if there is one
+    pub fn save_to(&self, dir: &str, name: &str, note: Option<&str>) -> Result<(), String> {
+        let mut save = self.to_json();
+        if let (Json::Object(fields), Some(note)) = (&mut save, note) {
+            fields.push(("note".to_string(), Json::Str(note.to_string())));
+        }
+
+        fs::create_dir_all(dir).map_err(|e| format!("Couldn't create {}: {}", dir, e))?;
+        write_atomically(&slot_path(dir, name), &save.to_string())
+    }
+
+    // a new recording starts from here. The hint level and the confirmations are kept with it, as chase and 'yes'
+    // don't do the same thing without them
+    pub fn start_recording(&mut self) {
+        let mut start = self.to_json();
+        if let Json::Object(fields) = &mut start {
+            fields.push(("hints".to_string(), Json::Str(self.hint_level().name().to_string())));
+            fields.push(("confirm_zero".to_string(), Json::Bool(self.confirm_zero)));
+            fields.push(("confirm_better".to_string(), Json::Bool(self.confirm_better)));
+        }
+        self.recording_start = start;
+        self.recording.clear();
+    }
+
+    // the game a recording started from, with the settings it was played under, and the moves to play on it
+    pub fn load_recording(path: &str, options: &GameOptions) -> Result<(Self, Vec<String>), String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+        let json = json::parse(&contents).map_err(|e| format!("{} is not a valid recording: {}", path, e))?;
+
+        let start = json.get("start").ok_or(format!("{} is not a recording", path))?;
+        let mut game = Game::from_json(start, options)?;
+        game.hint_override = Some(start.get("hints").and_then(Json::as_str).and_then(HintLevel::from_name).ok_or("Recording has an invalid 'hints'")?);
+        game.confirm_zero = start.get("confirm_zero").and_then(Json::as_bool).ok_or("Recording has an invalid 'confirm_zero'")?;
+        // recordings from before --confirm-better didn't have it
+        game.confirm_better = match start.get("confirm_better") {
+            Some(confirm) => confirm.as_bool().ok_or("Recording has an invalid 'confirm_better'")?,
+            None => false,
+        };
+
+        let moves = json
+            .get("moves")
+            .and_then(Json::as_array)
+            .ok_or("Recording has invalid 'moves'")?
+            .iter()
+            .map(|line| line.as_str().map(str::to_string).ok_or("Recording has an invalid move".to_string()))
+            .collect::<Result<Vec<String>, String>>()?;
+
+        Ok((game, moves))
+    }
+
+    // everything worth a bell goes through here, main rings it after the next draw
+    fn notify(&mut self, notice: Notice) {
+        self.notices.push(notice);
+    }
+
+    fn roll_unheld(&mut self) {
+        let rolled = self.current_roll.roll_with_holds(&mut self.rng);
+        self.stats.record_roll(rolled.len());
+        for face in rolled {
+            self.faces_rolled[face as usize] += 1;
+        }
+    }
+
+    // every turn starts with fresh dice, so the turn timer starts over here too
+    fn roll_fresh(&mut self) {
+        self.turn_started = time::Instant::now();
+        self.current_roll = Roll::new(&mut self.rng, &self.rules);
+        self.stats.record_roll(self.current_roll.dice.len());
+        for &face in &self.current_roll.dice {
+            self.faces_rolled[face as usize] += 1;
+        }
+    }
+
+    // what each open category would score with the dice as they are, or with brief hints just the best of them
+    pub fn preview_scores(&self) -> HashMap<ScoreType, u8> {
+        let score_table = &self.player().score_table;
+        let score = |score_type: &ScoreType| (*score_type, score_table.score_for(&self.current_roll, score_type, &self.rules));
+        match self.hint_level() {
+            HintLevel::Full => score_table.open_categories(&self.rules).iter().map(score).collect(),
+            HintLevel::Brief => score_table.best_open_score(&self.current_roll, &self.rules).iter().map(|(score_type, _)| score(score_type)).collect(),
+            HintLevel::Off => HashMap::new(),
+        }
+    }
+
+    // the categories the current player may use for a joker, None when the roll isn't one
+    fn joker_allowed_categories(&self, roll: &Roll) -> Option<Vec<ScoreType>> {
+        self.player().score_table.joker_categories(roll, &self.rules)
+    }
+
+    // plays the computer's next move, any holds it wants are set before the roll command it returns
+    pub fn ai_command(&mut self) -> Command {
+        match ai_choose(&self.current_roll, &self.player().score_table, self.rolls_left(), &self.rules) {
+            AiMove::Reroll(holds) => {
+                self.current_roll.holds = holds;
+                Command::Roll
+            },
+            // held back from scoring the dealt dice, it keeps what counts towards its pick and rolls the rest
+            AiMove::Score(score_type) if self.must_roll_first() => {
+                self.current_roll.hold_for(&score_type);
+                Command::Roll
+            },
+            AiMove::Score(score_type) => Command::Score(score_type),
+        }
+    }
+
+    // plays commands without drawing anything, the computer taking its own turns, until the game is over,
+    // 'quit' is given or the commands run out
+    pub fn run_headless(&mut self, mut commands: impl Iterator<Item = Command>) -> GameSummary {
+        let mut last_command: Option<Command> = None;
+
+        while self.game_state != GameStates::GameOver {
+            let command = if self.player().is_ai {
+                self.ai_command()
+            } else {
+                match commands.next() {
+                    Some(command) => command,
+                    None => break,
+                }
+            };
+            let Some(command) = resolve_repeat(command, &mut last_command) else { continue };
+
+            self.msg = match command {
+                Command::Quit => break,
+                Command::NotRecognised(msg) | Command::Help(msg) => msg,
+                // both need the terminal loop, which has the config and the colours
+                Command::Load(_) | Command::Theme(_) | Command::Playback(_) => "Not available when headless".to_string(),
+                command => self.attempt_command(&command).unwrap_or_else(|e| e.to_string()),
+            };
+        }
+
+        // there's no one to type a name for the leaderboard, and nothing is written to it from a headless run
+        if self.awaiting_name {
+            self.awaiting_name = false;
+            self.msg = "Game Over!".to_string();
+        }
+        self.record_pending = false;
+
+        GameSummary {
+            seed: self.seed,
+            game_over: self.game_state == GameStates::GameOver,
+            msg: self.msg.clone(),
+            cards: self
+                .players
+                .iter()
+                .map(|player| PlayerCard {
+                    name: player.name.clone(),
+                    scores: self.rules.categories().into_iter().map(|score_type| (score_type, player.score_table.table.get(&score_type).copied())).collect(),
+                    total: player.total(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.players[self.current_player]
+    }
+
+    // when the current turn runs out of time, None without a turn limit or while the computer plays
+    pub fn turn_deadline(&self) -> Option<time::Instant> {
+        if self.game_state != GameStates::Rolling || self.player().is_ai {
+            return None;
+        }
+        self.turn_limit.map(|limit| self.turn_started + limit)
+    }
+
+    pub fn time_left(&self) -> Option<time::Duration> {
+        self.turn_deadline().map(|deadline| deadline.saturating_duration_since(time::Instant::now()))
+    }
+
+    pub fn out_of_time(&self) -> bool {
+        self.time_left().is_some_and(|left| left.is_zero())
+    }
+
+    // what a turn that ran out of time gives up: the open category worth least at best, the first of any tie
+    pub fn timeout_scratch(&self) -> Option<ScoreType> {
+        let candidates = self.joker_allowed_categories(&self.current_roll).unwrap_or_else(|| self.player().score_table.open_categories(&self.rules));
+        candidates.into_iter().min_by_key(|score_type| score_type.max_score(&self.rules))
+    }
+
+    pub fn is_multiplayer(&self) -> bool {
+        self.players.len() > 1
+    }
+
+    fn turn(&self) -> usize {
+        (self.player().score_table.table.len() + 1).min(self.rules.categories().len())
+    }
+
+    pub fn rolls_left(&self) -> u8 {
+        self.rolls_per_turn.saturating_sub(self.rolls_used)
+    }
+
+    pub fn hint_level(&self) -> HintLevel {
+        self.hint_override.unwrap_or(self.session.hint_level())
+    }
+
+    pub fn window_title(&self) -> String {
+        let name = match &self.daily {
+            Some(date) => format!("Yacht Dice Daily {}", date),
+            None => "Yacht Dice".to_string(),
+        };
+        match self.game_state {
+            GameStates::GameOver => format!("{} — Game Over", name),
+            GameStates::Rolling => format!("{} — Turn {}", name, self.turn()),
+        }
+    }
+
+    pub fn status_line(&self) -> String {
+        if let Some(drill) = &self.drill {
+            return format!(
+                "Seed {} · Drill {} · Hits {}/{} · Rolls left {}",
+                self.seed,
+                drill.target.name(),
+                drill.successes,
+                drill.attempts,
+                self.rolls_left(),
+            );
+        }
+
+        let game = match &self.daily {
+            Some(date) => format!("Daily {}", date),
+            None => format!("Seed {}", self.seed),
+        };
+        let match_score = match &self.match_play {
+            Some(match_play) => format!(" · Match {}/{}", match_play.totals[self.current_player], match_play.target),
+            None => "".to_string(),
+        };
+        format!("{} · Turn {}/{} · Rolls left {}{}", game, self.turn(), self.rules.categories().len(), self.rolls_left(), match_score)
+    }
+
+    pub fn total(&self) -> u16 {
+        self.player().total()
+    }
+
+    // every player's name and total, in the order they play
+    pub fn final_scores(&self) -> Vec<(String, u16)> {
+        self.players.iter().map(|player| (player.name.clone(), player.total())).collect()
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            game_state: self.game_state,
+            rolls_used: self.rolls_used,
+            rng: self.rng.clone(),
+            current_roll: self.current_roll.clone(),
+            players: self.players.clone(),
+            current_player: self.current_player,
+            lucky_face: self.lucky_face,
+            faces_rolled: self.faces_rolled.clone(),
+            stats: self.stats.clone(),
+            drill: self.drill.clone(),
+            match_play: self.match_play.clone(),
+            seed: self.seed,
+            daily: self.daily.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.game_state = snapshot.game_state;
+        self.rolls_used = snapshot.rolls_used;
+        self.rng = snapshot.rng;
+        self.current_roll = snapshot.current_roll;
+        self.players = snapshot.players;
+        self.current_player = snapshot.current_player;
+        self.lucky_face = snapshot.lucky_face;
+        self.faces_rolled = snapshot.faces_rolled;
+        self.stats = snapshot.stats;
+        self.drill = snapshot.drill;
+        self.match_play = snapshot.match_play;
+        self.seed = snapshot.seed;
+        self.daily = snapshot.daily;
+    }
+
+    fn start_new_game(&mut self, command: &Command) {
+        let from = self.state_label();
+        if let Some(drill) = &mut self.drill {
+            *drill = Drill::new(drill.target);
+        }
+        for player in &mut self.players {
+            player.reset();
+        }
+        self.current_player = 0;
+        if self.lucky_face.is_some() {
+            self.lucky_face = Some(self.rng.gen_range(1..=self.rules.sides));
+        }
+        self.faces_rolled = vec![0; self.rules.sides as usize + 1];
+        self.stats = Stats::default();
+        // a fresh roll so holds from the last game don't carry over
+        self.roll_fresh();
+        self.game_state = GameStates::Rolling;
+        self.rolls_used = 1;
+        self.log_transition(&from, command);
+        self.start_recording();
+    }
+
+    // refused moves are recorded too, as they can still let a score waiting on 'yes' go. The computer's moves
+    // aren't, it makes the same ones again when the recording is played back
+    pub fn attempt_command(&mut self, command: &Command) -> Result<String, CommandError> {
+        let line = if self.player().is_ai { None } else { command.to_line() };
+        let result = self.apply_command(command);
+        if let Some(line) = line {
+            self.recording.push(line);
+        }
+        result
+    }
+
+    fn apply_command(&mut self, command: &Command) -> Result<String, CommandError> {
+        // anything other than the confirmation itself lets a waiting score go
+        let pending = self.pending_score.take();
+        if let Some(score_type) = pending {
+            match command {
+                Command::Confirm => {
+                    self.pending_score = Some(score_type);
+                    return self.apply_command(&Command::Score(score_type));
+                },
+                Command::Cancel => return Ok(format!("Not scoring {}", score_type.name())),
+                _ => {},
+            }
+        }
+
+        // a finished card can still be looked over, saved or shared, but nothing on it changes until the next game
+        if self.game_state == GameStates::GameOver && !command.allowed_after_game_over() {
+            return Err(CommandError::GameOver);
+        }
+
+        // only a move that went through and changed something takes up an undo slot
+        let undoable = matches!(command, Command::Roll | Command::ReRoll(_) | Command::Sort | Command::Score(_) | Command::Scratch(_) | Command::HoldMany(..) | Command::ClearHolds | Command::ChaseBonus | Command::New | Command::Daily);
+        let before = undoable.then(|| self.snapshot());
+        let result = self.carry_out(command, pending);
+        if let Some(before) = before {
+            if result.is_ok() && before != self.snapshot() {
+                if self.history.len() == UNDO_LIMIT {
+                    self.history.pop_front();
+                }
+                self.history.push_back(before);
+            }
+        }
+        result
+    }
+
+    // pending is the score that was waiting on a 'yes', if this is it being confirmed
+    fn carry_out(&mut self, command: &Command, pending: Option<ScoreType>) -> Result<String, CommandError> {
+        match command {
+            Command::Confirm | Command::Cancel => Err(CommandError::NothingToConfirm),
+            Command::Undo => {
+                let Some(snapshot) = self.history.pop_back() else {
+                    return Err(CommandError::NothingToUndo)
+                };
+                self.restore(snapshot);
+                Ok(format!("Undone, {} more move{} can be taken back", self.history.len(), if self.history.len() == 1 { "" } else { "s" }))
+            },
+            Command::Roll => {
+                if self.is_final_roll() {
+                    return Err(CommandError::NoRollsLeft)
+                }
+                // nothing would change, except for the roll a --roll-first turn has to make anyway
+                if self.current_roll.holds.iter().all(|&held| held) && !self.must_roll_first() {
+                    return Err(CommandError::AllHeld)
+                }
+
+                self.roll_unheld();
+
+                self.advance_gamestate(command);
+
+                Ok("Onto next roll".to_string())
+            },
+            // the dice named are rolled and the rest kept, whatever is held, and the holds are left as they were
+            Command::ReRoll(nums) => {
+                if self.is_final_roll() {
+                    return Err(CommandError::NoRollsLeft)
+                }
+
+                let holds = std::mem::replace(&mut self.current_roll.holds, vec![true; self.current_roll.dice.len()]);
+                for num in nums {
+                    self.current_roll.holds[*num as usize] = false;
+                }
+                self.roll_unheld();
+                self.current_roll.holds = holds;
+
+                self.advance_gamestate(command);
+
+                let rerolled: Vec<String> = nums.iter().map(|&num| (num as u8 + 1).to_string()).collect();
+                Ok(format!("Rerolled dice {}", rerolled.join(", ")))
+            },
+            Command::ClearHolds => {
+                self.current_roll.reset_holds();
+                Ok("All dice unheld".to_string())
+            },
+            Command::Sort => {
+                self.current_roll.sort();
+                Ok("Dice Sorted!".to_string())
+            },
+            Command::Score(score_type) | Command::Scratch(score_type) => {
+                let from = self.state_label();
+                let scratch = matches!(command, Command::Scratch(_));
+                if self.must_roll_first() {
+                    return Err(CommandError::MustRollFirst);
+                }
+                let score_table = &self.player().score_table;
+                let wasted = !score_table.check_table(score_type) && score_table.score_for(&self.current_roll, score_type, &self.rules) == 0;
+                if self.confirm_zero && !scratch && !self.player().is_ai && self.drill.is_none() && wasted && pending != Some(*score_type) {
+                    self.pending_score = Some(*score_type);
+                    return Ok(format!("{} would score 0 with these dice, type 'yes' to score it anyway or 'no' to pick again", score_type.name()));
+                }
+
+                // drilling scores every attempt against the target, leaving the table alone
+                if let Some(drill) = &mut self.drill {
+                    let hit = !scratch && qualifies(&self.current_roll, &drill.target, &self.rules);
+                    drill.record(hit, self.rolls_used);
+                    let summary = drill.summary();
+
+                    self.rolls_used = 1;
+                    self.roll_fresh();
+                    self.log_transition(&from, command);
+
+                    return Ok(format!("{} {}", if hit { "Hit!" } else { "Missed." }, summary));
+                }
+
+                if let Some(allowed) = self.joker_allowed_categories(&self.current_roll) {
+                    if !allowed.contains(score_type) && !self.player().score_table.check_table(score_type) {
+                        return Err(CommandError::JokerElsewhere(allowed));
+                    }
+                }
+
+                // like a yacht put in Chance, when an open category would score more with these dice
+                if self.confirm_better && !scratch && !self.player().is_ai && pending != Some(*score_type) {
+                    let score_table = &self.player().score_table;
+                    if let Some((better, best)) = score_table.best_open_score(&self.current_roll, &self.rules) {
+                        let chosen = score_table.score_for(&self.current_roll, score_type, &self.rules) as u16;
+                        if !score_table.check_table(score_type) && best > chosen {
+                            self.pending_score = Some(*score_type);
+                            return Ok(format!(
+                                "{} would score {} but {} would score {}, type 'yes' to score it anyway or 'no' to pick again",
+                                score_type.name(), chosen, better.name(), best
+                            ));
+                        }
+                    }
+                }
+
+                let lucky = !scratch && self.is_lucky_roll();
+                let turn = self.turn() as u8;
+                // judged before the score goes in, while the chosen category still counts as open
+                let coaching = if self.coach { coach_note(&self.player().score_table, &self.current_roll, score_type, &self.rules) } else { None };
+                let coaching = coaching.map_or("".to_string(), |note| format!(" {}", note));
+                let finished_so_far = self.players.iter().filter(|player| player.finished_at.is_some()).count() as u32;
+                let bonuses_before = (self.player().score_table.upper_bonus(), self.player().score_table.yacht_bonus);
+                let player = &mut self.players[self.current_player];
+                let filled = if scratch {
+                    player.score_table.scratch_on_table(score_type)
+                } else {
+                    player.score_table.score_on_table(score_type, &self.current_roll, &self.rules)
+                };
+                if filled {
+                    player.score_table.turn_filled.insert(*score_type, turn);
+                    if let Some(&score) = player.score_table.table.get(score_type) {
+                        self.stats.record_score(*score_type, score);
+                    }
+                    player.rolls_taken += self.rolls_used as u32;
+                    if lucky {
+                        player.lucky_bonus += LUCKY_BONUS;
+                    }
+                    if player.score_table.table.len() == self.rules.categories().len() {
+                        player.finished_at = Some(finished_so_far + 1);
+                    }
+
+                    let score_table = &player.score_table;
+                    let yacht = (*score_type == ScoreType::Yacht && score_table.table.get(score_type).is_some_and(|&score| score > 0)) || score_table.yacht_bonus > bonuses_before.1;
+                    let upper_bonus = score_table.upper_bonus() > bonuses_before.0;
+                    if yacht {
+                        self.notify(Notice::Yacht);
+                    }
+                    if upper_bonus {
+                        self.notify(Notice::UpperBonus);
+                    }
+
+                    if self.players.iter().all(|player| player.finished_at.is_some()) {
+                        self.session.games_played += self.players.len() as u32;
+                        self.session.total_points += self.players.iter().map(|player| player.total() as u32).sum::<u32>();
+
+                        // in a match the next game starts straight away, until someone reaches the target
+                        let mut match_won = "".to_string();
+                        if let Some(match_play) = &mut self.match_play {
+                            let totals: Vec<u16> = self.players.iter().map(Player::total).collect();
+                            match_play.record(&totals);
+                            let names: Vec<&str> = self.players.iter().map(|player| player.name.as_str()).collect();
+                            let standing = match_play.standing(&names);
+                            let games = match_play.games;
+                            match match_play.winner() {
+                                None => {
+                                    self.start_new_game(command);
+                                    return Ok(format!("Game {} of the match is over, {}. Next game!{}", games, standing, coaching));
+                                },
+                                Some(winner) if self.is_multiplayer() => match_won = format!(" {} wins the match, {}.", self.players[winner].name, standing),
+                                Some(_) => match_won = format!(" Match target reached in {} games.", games),
+                            }
+                        }
+
+                        self.game_state = GameStates::GameOver;
+                        self.notify(Notice::GameOver);
+                        self.log_transition(&from, command);
+                        if self.is_multiplayer() {
+                            let ranked = rank_players(&self.players);
+                            self.panel = standings_panel(&ranked);
+                            self.panel.extend(dice_histogram_panel(&self.faces_rolled));
+                            self.panel.push(self.stats.summary());
+                            self.record_pending = true;
+                            return Ok(format!("Game Over! {} wins!{} Type 'new' to start a new game!{}", ranked[0].name, match_won, coaching));
+                        }
+                        self.panel = section_panel(self.player());
+                        self.panel.extend(dice_histogram_panel(&self.faces_rolled));
+                        self.panel.push(self.stats.summary());
+                        self.awaiting_name = true;
+                        return Ok(format!("Game Over!{} Type a name for the leaderboard, or leave it blank to skip{}", match_won, coaching));
+                    }
+
+                    self.current_player = (self.current_player + 1) % self.players.len();
+                    self.game_state = GameStates::Rolling;
+                    self.rolls_used = 1;
+                    self.roll_fresh();
+                    self.log_transition(&from, command);
+
+                    let lucky = if lucky { format!(" Lucky bonus +{}", LUCKY_BONUS) } else { "".to_string() };
+                    let submitted = if scratch { format!("{} scratched for 0.", score_type.name()) } else { "Score submitted!".to_string() };
+                    if self.is_multiplayer() {
+                        return Ok(format!("{}{} {}'s turn{}", submitted, lucky, self.player().name, coaching));
+                    }
+                    Ok(format!("{}{}{}", submitted, lucky, coaching))
+                } else {
+                    Err(CommandError::AlreadyScored)
+                }
+            },
+            Command::HoldMany(nums, rejected) => {
+                for num in nums {
+                    self.current_roll.toggle_hold(num);
+                }
+
+                let toggled: Vec<String> = nums.iter().map(|&num| (num as u8 + 1).to_string()).collect();
+                let mut msg = if toggled.is_empty() { "No dice toggled".to_string() } else { format!("Toggled dice {}", toggled.join(", ")) };
+                if !rejected.is_empty() {
+                    msg.push_str(&format!(", ignored {} (dice are 1-{})", rejected.join(", "), self.rules.dice));
+                }
+                Ok(msg)
+            },
+            Command::ChaseBonus => {
+                if self.hint_level() == HintLevel::Off {
+                    return Err(CommandError::HintsOff)
+                }
+
+                if self.is_final_roll() {
+                    return Err(CommandError::NoRollsLeft)
+                }
+
+                let upper_total = self.player().score_table.upper_total();
+                if upper_total >= UPPER_BONUS_THRESHOLD {
+                    return Err(CommandError::BonusReached)
+                }
+
+                let Some(face) = self.bonus_target() else {
+                    return Err(CommandError::NoUpperOpen)
+                };
+
+                self.current_roll.hold_face(face);
+                self.roll_unheld();
+
+                self.advance_gamestate(command);
+
+                Ok(format!("Chasing {}s for the upper bonus, {} more points needed", face, UPPER_BONUS_THRESHOLD - upper_total))
+            },
+            Command::BonusOdds => {
+                if self.hint_level() == HintLevel::Off {
+                    return Err(CommandError::HintsOff)
+                }
+
+                let upper_total = self.player().score_table.upper_total();
+                if upper_total >= UPPER_BONUS_THRESHOLD {
+                    return Err(CommandError::BonusReached)
+                }
+
+                let open = self.player().score_table.open_upper_faces().len();
+                let odds = self.bonus_odds();
+                Ok(format!("About {:.0}% chance of the upper bonus, {} more points needed from {} open upper categories", odds * 100.0, UPPER_BONUS_THRESHOLD - upper_total, open))
+            },
+            Command::Odds(score_type) => {
+                if self.hint_level() == HintLevel::Off {
+                    return Err(CommandError::HintsOff)
+                }
+                if self.is_final_roll() {
+                    return Err(CommandError::NoRollsLeft)
+                }
+
+                let odds = completion_probability(&self.current_roll, score_type, &self.rules);
+                Ok(format!("{:.1}% chance the next roll makes {} with these dice held", odds * 100.0, score_type.name()))
+            },
+            Command::Info(None) => {
+                self.panel = scoring_panel(&self.rules);
+                Ok("How each category scores, type 'info <type>' for one of them".to_string())
+            },
+            Command::Info(Some(score_type)) => Ok(format!("{}: {}", score_type.name(), score_type.rule(&self.rules))),
+            Command::Verify if self.debug => {
+                let cases = verify_cases(&self.rules).len();
+                let mismatches = verify_scoring(&self.rules);
+                let passed = cases - mismatches.len();
+                self.panel = mismatches;
+                Ok(format!("Verify: {}/{} {} scoring cases passed", passed, cases, self.rules.ruleset.name()))
+            },
+            Command::Verify => Err(CommandError::NotRecognised("Invalid command, try 'help' for list of commands".to_string())),
+            Command::AllScores => {
+                let hint_level = self.hint_level();
+                if hint_level == HintLevel::Off {
+                    return Err(CommandError::HintsOff)
+                }
+
+                let score_table = &self.player().score_table;
+                let mut panel = all_scores_panel(&score_table.all_scores(&self.current_roll, &self.rules));
+                if hint_level == HintLevel::Full {
+                    if let Some((score_type, score)) = score_table.best_open_score(&self.current_roll, &self.rules) {
+                        panel.push(format!("Best open category: {} for {}", score_type.name(), score));
+                    }
+                }
+                self.panel = panel;
+                Ok("Scores for the current dice, used categories are marked".to_string())
+            },
+            Command::Commands => {
+                self.panel = commands_panel();
+                Ok("Every command, type 'help <command>' for more on one".to_string())
+            },
+            Command::Save(name, note) => {
+                self.save_to(SAVE_DIR, name, note.as_deref())?;
+                Ok(format!("Saved as '{}'", name))
+            },
+            Command::Export(path) => {
+                if self.game_state != GameStates::GameOver {
+                    return Err(CommandError::ExportTooEarly);
+                }
+
+                let players = self
+                    .players
+                    .iter()
+                    .map(|player| {
+                        let mut card = player.score_table.scorecard_json(&self.rules);
+                        if let Json::Object(fields) = &mut card {
+                            fields.insert(0, ("name".to_string(), Json::Str(player.name.clone())));
+                            fields.push(("lucky_bonus".to_string(), Json::number(player.lucky_bonus)));
+                            fields.push(("grand_total".to_string(), Json::number(player.total())));
+                        }
+                        card
+                    })
+                    .collect();
+                let export = Json::Object(vec![("seed".to_string(), Json::number(self.seed)), ("players".to_string(), Json::Array(players))]);
+
+                write_atomically(path, &export.to_string())?;
+                Ok(format!("Scorecard exported to {}", path))
+            },
+            Command::Record(path) => {
+                let recording = Json::Object(vec![
+                    ("start".to_string(), self.recording_start.clone()),
+                    ("moves".to_string(), Json::Array(self.recording.iter().map(|line| Json::Str(line.clone())).collect())),
+                ]);
+
+                write_atomically(path, &recording.to_string())?;
+                Ok(format!("{} moves recorded to {}, 'playback {}' plays them again", self.recording.len(), path, path))
+            },
+            Command::Stats => {
+                self.panel = self.stats.panel(&self.rules);
+                Ok("Stats for this game so far".to_string())
+            },
+            Command::Leaderboard => {
+                self.panel = leaderboard_panel();
+                Ok("Best scores recorded on this machine".to_string())
+            },
+            Command::Saves => {
+                self.panel = saves_panel(SAVE_DIR);
+                Ok("Saved games and their notes".to_string())
+            },
+            Command::Preview => {
+                if self.hint_level() == HintLevel::Off {
+                    return Err(CommandError::HintsOff)
+                }
+
+                self.preview = true;
+                Ok("What the open score types would get, shown beside the table".to_string())
+            },
+            Command::Want(score_type) => {
+                self.highlight = Some(*score_type);
+                let count = self.current_roll.suggested_holds(score_type).iter().filter(|&&wanted| wanted).count();
+                Ok(format!("{} wants the {} highlighted dice held, it would score {} now", score_type.name(), count, evaluate_score(&self.current_roll, score_type, &self.rules)))
+            },
+            Command::Replay(seed) => {
+                if self.game_state != GameStates::GameOver {
+                    return Err(CommandError::ReplayTooEarly)
+                }
+
+                let seed = seed.unwrap_or_else(rand::random);
+                let mut panel = vec![format!("YOUR CHOICES REPLAYED ON SEED {}", seed)];
+                for player in &self.players {
+                    let mut choices: Vec<(u8, ScoreType)> = player.score_table.turn_filled.iter().map(|(score_type, &turn)| (turn, *score_type)).collect();
+                    choices.sort_by_key(|&(turn, _)| turn);
+                    if choices.len() != self.rules.categories().len() {
+                        panel.push(format!("{}: choice order wasn't recorded for this game", player.name));
+                        continue;
+                    }
+
+                    let choices: Vec<ScoreType> = choices.into_iter().map(|(_, score_type)| score_type).collect();
+                    let replayed = replay_choices(&choices, seed, self.rolls_per_turn, &self.rules);
+                    let actual = player.score_table.table_total();
+                    panel.push(format!("{:<16} replayed {:>4}  played {:>4}  ({:+})", player.name, replayed, actual, replayed as i32 - actual as i32));
+                }
+                self.panel = panel;
+                Ok("Same choices, different dice. Type 'new' to start a new game!".to_string())
+            },
+            Command::Coach => {
+                self.coach = !self.coach;
+                Ok(format!("Coach mode {}", if self.coach { "on, each score will get a critique" } else { "off" }))
+            },
+            Command::Hints(level) => {
+                self.hint_override = *level;
+                match level {
+                    Some(level) => Ok(format!("Hints set to {}", level.name())),
+                    None => Ok(format!("Hints adapt to your scores, currently {}", self.hint_level().name())),
+                }
+            },
+            Command::New => {
+                self.daily = None;
+                if let Some(match_play) = &mut self.match_play {
+                    *match_play = MatchPlay::new(match_play.target, self.players.len());
+                }
+                self.start_new_game(command);
+                Ok("New Game Started".to_string())
+            },
+            Command::Daily => {
+                let (year, month, day) = civil_date(days_since_epoch());
+                self.seed = daily_seed(year, month, day);
+                self.rng = ChaCha12Rng::seed_from_u64(self.seed);
+                let date = format!("{:04}-{:02}-{:02}", year, month, day);
+                self.daily = Some(date.clone());
+                if let Some(match_play) = &mut self.match_play {
+                    *match_play = MatchPlay::new(match_play.target, self.players.len());
+                }
+                self.start_new_game(command);
+                Ok(format!("Daily challenge for {} started, everyone playing today gets these dice", date))
+            },
+            Command::NotRecognised(msg) => Err(CommandError::NotRecognised(msg.clone())),
+            Command::Help(msg) => Ok(msg.clone()),
+            // these need whatever is driving the game, the terminal loop has the config and the input they work with
+            Command::Quit | Command::Repeat | Command::Load(_) | Command::Theme(_) | Command::Playback(_) => Err(CommandError::NotAvailable),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    Roll,
+    // rolls just these dice, leaving the holds alone
+    ReRoll(Vec<DiceNum>),
+    Sort,
+    Score(ScoreType),
+    Scratch(ScoreType),
+    AllScores,
+    ChaseBonus,
+    Preview,
+    Want(ScoreType),
+    Replay(Option<u64>),
+    Coach,
+    BonusOdds,
+    Odds(ScoreType),
+    // the whole scoring table, or just the one category
+    Info(Option<ScoreType>),
+    Hints(Option<HintLevel>),
+    Commands,
+    Save(String, Option<String>),
+    Saves,
+    Leaderboard,
+    Stats,
+    Export(String),
+    Record(String),
+    Playback(String),
+    Daily,
+    Theme(String),
+    Load(String),
+    Verify,
+    // dice to toggle, and whatever was typed that isn't a die
+    HoldMany(Vec<DiceNum>, Vec<String>),
+    ClearHolds,
+    New,
+    Quit,
+    Undo,
+    Repeat,
+    Confirm,
+    Cancel,
+    Help(String),
+    NotRecognised(String),
+}
+
+impl Command {
+    // what's left to do once the game is over, rolling is turned into 'new' by the terminal loop before it gets here
+    fn allowed_after_game_over(&self) -> bool {
+        matches!(self, Command::New | Command::Daily | Command::Replay(_) | Command::Info(_) | Command::Hints(_) | Command::Coach | Command::Commands | Command::Save(..) | Command::Saves | Command::Leaderboard | Command::Stats | Command::Export(_) | Command::Record(_) | Command::Playback(_) | Command::Theme(_) | Command::Load(_) | Command::Verify | Command::Quit | Command::Repeat | Command::Help(_) | Command::NotRecognised(_))
+    }
+
+    // the line that parses back into this command, for the moves a recording needs. Anything else leaves the
+    // dice and the card alone, and new or daily start a recording of their own
+    fn to_line(&self) -> Option<String> {
+        match self {
+            Command::Roll => Some("roll".to_string()),
+            Command::ReRoll(nums) => {
+                let nums: Vec<String> = nums.iter().map(|&num| (num as u8 + 1).to_string()).collect();
+                Some(format!("reroll {}", nums.join(" ")))
+            },
+            Command::Sort => Some("sort".to_string()),
+            Command::Score(score_type) => Some(format!("score {}", score_type.key())),
+            Command::Scratch(score_type) => Some(format!("scratch {}", score_type.key())),
+            Command::ChaseBonus => Some("chase".to_string()),
+            Command::Hints(level) => Some(format!("hints {}", level.map_or("auto", |level| level.name()))),
+            Command::HoldMany(nums, _) => {
+                let nums: Vec<String> = nums.iter().map(|&num| (num as u8 + 1).to_string()).collect();
+                Some(format!("hold {}", nums.join(" ")))
+            },
+            Command::ClearHolds => Some("unhold".to_string()),
+            Command::Undo => Some("undo".to_string()),
+            Command::Confirm => Some("yes".to_string()),
+            Command::Cancel => Some("no".to_string()),
+            _ => None,
+        }
+    }
+}
+
+// every command the parser understands, the one place its words, usage and help text live
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub usage: &'static str,
+    pub help: &'static str,
+    pub hidden: bool,
+}
+
+pub const COMMANDS: &[CommandInfo] = &[
+    CommandInfo { name: "roll", aliases: &["r"], usage: "roll", help: "rolls the dice that aren't held. Counts as a roll!", hidden: false },
+    CommandInfo { name: "reroll", aliases: &[], usage: "reroll <dice>", help: "rolls only dice number <dice>, keeping the rest whatever is held. Counts as a roll!", hidden: false },
+    CommandInfo { name: "sort", aliases: &["s"], usage: "sort", help: "sorts the dice lowest to highest, held dice stay held", hidden: false },
+    CommandInfo { name: "hold", aliases: &["h"], usage: "hold <dice>", help: "holds each of dice <dice> that isn't held, excluding it from next rolls, and lets go of each one that is", hidden: false },
+    CommandInfo { name: "unhold", aliases: &["uh"], usage: "unhold", help: "lets go of every held die", hidden: false },
+    CommandInfo { name: "score", aliases: &["sc"], usage: "score <type>", help: "submits dice to score where <type> is the number of that score type or its name, shortened as far as it stays clear", hidden: false },
+    CommandInfo { name: "scratch", aliases: &[], usage: "scratch <type>", help: "takes 0 in score type <type> on purpose, whatever the dice", hidden: false },
+    CommandInfo { name: "scores", aliases: &[], usage: "scores", help: "shows what the dice would score in every category, used or not", hidden: false },
+    CommandInfo { name: "chase", aliases: &[], usage: "chase", help: "holds the dice of the best open upper face and rolls the rest, aiming for the upper bonus", hidden: false },
+    CommandInfo { name: "bonusodds", aliases: &["bo"], usage: "bonusodds", help: "estimates the chance of still reaching the upper bonus with the upper categories left", hidden: false },
+    CommandInfo { name: "odds", aliases: &[], usage: "odds <type>", help: "gives the exact chance that rolling the dice that aren't held makes score type <type>", hidden: false },
+    CommandInfo { name: "info", aliases: &[], usage: "info <type>", help: "shows how every category scores and what it's worth, or just score type <type>", hidden: false },
+    CommandInfo { name: "preview", aliases: &["p"], usage: "preview", help: "shows beside the table what each open score type would get with these dice", hidden: false },
+    CommandInfo { name: "want", aliases: &["w"], usage: "want <type>", help: "picks out the dice worth holding to go for score type <type>, without holding them", hidden: false },
+    CommandInfo { name: "replay", aliases: &[], usage: "replay <seed>", help: "once a game is over, plays your category choices again on dice from <seed> (or a random one) to see how much was luck", hidden: false },
+    CommandInfo { name: "coach", aliases: &[], usage: "coach", help: "turns coach mode on or off, which says after each score whether another open category would have scored more", hidden: false },
+    CommandInfo { name: "hints", aliases: &[], usage: "hints <level>", help: "sets how much help scores, preview and chase give (full, brief, off or auto), auto fades as you improve", hidden: false },
+    CommandInfo { name: "commands", aliases: &[], usage: "commands", help: "lists every command with its shortcuts", hidden: false },
+    CommandInfo { name: "verify", aliases: &[], usage: "verify", help: "checks scoring against known rolls (needs --debug)", hidden: true },
+    CommandInfo { name: "save", aliases: &[], usage: "save <name> \"<note>\"", help: "saves the game under <name>, with an optional quoted note to remember it by", hidden: false },
+    CommandInfo { name: "load", aliases: &[], usage: "load <name>", help: "picks up the game saved under <name>, replacing this one", hidden: false },
+    CommandInfo { name: "leaderboard", aliases: &["top"], usage: "leaderboard", help: "shows the ten best recorded scores", hidden: false },
+    CommandInfo { name: "export", aliases: &[], usage: "export <file>", help: "once the game is over, writes every scorecard to <file> as JSON", hidden: false },
+    CommandInfo { name: "record", aliases: &[], usage: "record <file>", help: "writes this game's moves so far to <file>, handy to send along with a bug report", hidden: false },
+    CommandInfo { name: "playback", aliases: &[], usage: "playback <file>", help: "plays the game recorded in <file> back one move at a time, replacing this one", hidden: false },
+    CommandInfo { name: "stats", aliases: &[], usage: "stats", help: "shows rolls, dice and the average score in each category so far this game", hidden: false },
+    CommandInfo { name: "saves", aliases: &[], usage: "saves", help: "lists saved games and their notes", hidden: false },
+    CommandInfo { name: "daily", aliases: &[], usage: "daily", help: "starts today's daily challenge, the same dice for everyone playing on this date", hidden: false },
+    CommandInfo { name: "theme", aliases: &[], usage: "theme <name>", help: "switches the board's colours to theme <name>: classic, contrast or mono", hidden: false },
+    CommandInfo { name: "new", aliases: &[], usage: "new", help: "starts a new game, refreshing the scores", hidden: false },
+    CommandInfo { name: "yes", aliases: &["y"], usage: "yes", help: "goes ahead with a score waiting to be confirmed (with --confirm-zero or --confirm-better)", hidden: false },
+    CommandInfo { name: "no", aliases: &["n"], usage: "no", help: "drops a score waiting to be confirmed", hidden: false },
+    CommandInfo { name: "undo", aliases: &["u"], usage: "undo", help: "takes back your last move, up to five moves back", hidden: false },
+    CommandInfo { name: "repeat", aliases: &["."], usage: "repeat", help: "does the last command again, handy for rolling", hidden: false },
+    CommandInfo { name: "quit", aliases: &[], usage: "quit", help: "quits the game", hidden: false },
+    CommandInfo { name: "help", aliases: &[], usage: "help <command>", help: "shows possible commands or help for <command> (but you know that...)", hidden: false },
+];
+
+pub fn find_command(word: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|info| info.name == word || info.aliases.contains(&word))
+}
+
+// the command to carry out for the one given, which is the last one again for repeat, None if nothing has been
+// done yet to repeat. Repeat is never stored itself, so it can only ever stand in for a real command
+pub fn resolve_repeat(command: Command, last_command: &mut Option<Command>) -> Option<Command> {
+    if command == Command::Repeat {
+        return last_command.clone();
+    }
+    if !matches!(command, Command::NotRecognised(_) | Command::Help(_)) {
+        *last_command = Some(command.clone());
+    }
+    Some(command)
+}
+
+// why a command was refused, the game is left as it was
+#[derive(Debug, PartialEq, Clone)]
+pub enum CommandError {
+    AlreadyScored,
+    NoRollsLeft,
+    AllHeld,
+    MustRollFirst,
+    // a joker yacht has to go in one of these
+    JokerElsewhere(Vec<ScoreType>),
+    HintsOff,
+    BonusReached,
+    NoUpperOpen,
+    NothingToUndo,
+    NothingToConfirm,
+    ExportTooEarly,
+    ReplayTooEarly,
+    // the game is over and the command would change the card
+    GameOver,
+    NotRecognised(String),
+    NotAvailable,
+    // a save or export that couldn't be written
+    Io(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::AlreadyScored => write!(f, "That score type was already used!"),
+            CommandError::NoRollsLeft => write!(f, "No more rolls available this round, try 'score'"),
+            CommandError::AllHeld => write!(f, "All dice are held, try 'score' or 'unhold' some first"),
+            CommandError::MustRollFirst => write!(f, "Roll at least once before scoring this turn"),
+            CommandError::JokerElsewhere(allowed) => {
+                let names: Vec<&str> = allowed.iter().map(ScoreType::name).collect();
+                write!(f, "Joker! That yacht has to go in {}", names.join(", "))
+            },
+            CommandError::HintsOff => write!(f, "Hints are off, type 'hints full' to turn them back on"),
+            CommandError::BonusReached => write!(f, "Upper bonus already reached"),
+            CommandError::NoUpperOpen => write!(f, "No upper categories left open to chase the bonus with"),
+            CommandError::NothingToUndo => write!(f, "Nothing to undo"),
+            CommandError::NothingToConfirm => write!(f, "Nothing waiting to be confirmed"),
+            CommandError::ExportTooEarly => write!(f, "Scorecards can be exported once the game is over"),
+            CommandError::ReplayTooEarly => write!(f, "Replay needs a finished game, fill the table first"),
+            CommandError::GameOver => write!(f, "The game is over, type 'new' or 'roll' to start another"),
+            CommandError::NotAvailable => write!(f, "That command isn't available here"),
+            CommandError::NotRecognised(msg) | CommandError::Io(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+// so file errors can still be passed up with '?'
+impl From<String> for CommandError {
+    fn from(msg: String) -> Self {
+        CommandError::Io(msg)
+    }
+}
+
+// how the final total was made up, for the game over panel
+fn section_panel(player: &Player) -> Vec<String> {
+    let score_table = &player.score_table;
+    let lucky = if player.lucky_bonus > 0 { format!("  Lucky bonus {}", player.lucky_bonus) } else { "".to_string() };
+    vec![
+        format!("FINAL SCORE   Upper {}  Upper bonus {}  Lower {}", score_table.upper_total(), score_table.upper_bonus(), score_table.lower_total()),
+        format!("              Yacht bonus {}{}  Total {}", score_table.yacht_bonus, lucky, player.total()),
+    ]
+}
+
+// bars for how often each face came up, two faces to a row so it fits under the standings
+fn dice_histogram_panel(faces_rolled: &[u32]) -> Vec<String> {
+    const BAR_WIDTH: u32 = 12;
+
+    let most = faces_rolled.iter().copied().max().unwrap_or(0).max(1);
+    let bar = |face: usize| {
+        let count = faces_rolled[face];
+        format!("{}s {:<12}{:>4}", face, "█".repeat((count * BAR_WIDTH / most) as usize), count)
+    };
+
+    let mut lines = vec![format!("DICE ROLLED THIS GAME   ({} in all)", faces_rolled.iter().sum::<u32>())];
+    let sides = faces_rolled.len() - 1;
+    let half = sides.div_ceil(2);
+    for face in 1..=half {
+        if face + half <= sides {
+            lines.push(format!("{}     {}", bar(face), bar(face + half)));
+        } else {
+            lines.push(bar(face));
+        }
+    }
+
+    lines
+}
+
+fn days_since_epoch() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs() / 86_400)
+}
+
+// the UTC calendar date a count of days since 1970-01-01 falls on, using Howard Hinnant's days_from_civil in reverse
+fn civil_date(days: u64) -> (u32, u32, u32) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (year_of_era + era * 400 + u64::from(month <= 2)) as u32;
+    (year, month, day)
+}
+
+// the date written out as a number, so a daily challenge can be played again later with --seed 20240131
+fn daily_seed(year: u32, month: u32, day: u32) -> u64 {
+    (year * 10_000 + month * 100 + day) as u64
+}
+
+#[derive(Debug, PartialEq)]
+enum AiMove {
+    Reroll(Vec<bool>),
+    Score(ScoreType),
+}
+
+// scores straight away once the dice are worth a full house or better in an open category, or when out of rolls,
+// otherwise keeps the most common face (ties to the higher) and rolls the rest. Five of a kind with nowhere
+// good to put them is scored too, as there'd be nothing left to roll
+fn ai_choose(roll: &Roll, table: &ScoreTable, rolls_left: u8, rules: &Rules) -> AiMove {
+    let Some((best, score)) = table.best_open_score(roll, rules) else {
+        return AiMove::Score(ScoreType::Chance);
+    };
+
+    let counts = roll.face_counts();
+    let keep = (1..=roll.sides).max_by_key(|&face| (counts[face as usize], face)).unwrap_or(roll.sides);
+
+    if rolls_left == 0 || score >= 25 || counts[keep as usize] as usize == roll.dice.len() {
+        return AiMove::Score(best);
+    }
+
+    AiMove::Reroll(roll.dice.iter().map(|&x| x == keep).collect())
+}
+
+// plays the categories in the order given, each turn going after that category with fresh dice from the seed
+fn replay_choices(choices: &[ScoreType], seed: u64, rolls_per_turn: u8, rules: &Rules) -> u16 {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    let mut score_table = ScoreTable::new();
+
+    for score_type in choices {
+        let mut roll = Roll::new(&mut rng, rules);
+        for _ in 1..rolls_per_turn {
+            roll.hold_for(score_type);
+            roll.roll_with_holds(&mut rng);
+        }
+        score_table.score_on_table(score_type, &roll, rules);
+    }
+
+    score_table.table_total()
+}
+
+// how the chosen category compares with the best one still open for the same dice
+fn coach_note(score_table: &ScoreTable, roll: &Roll, chosen: &ScoreType, rules: &Rules) -> Option<String> {
+    if score_table.check_table(chosen) {
+        return None;
+    }
+
+    let (best, best_score) = score_table.best_open_score(roll, rules)?;
+    let score = evaluate_score(roll, chosen, rules) as u16;
+
+    if best_score > score {
+        Some(format!("Coach: {} scored {}, but {} was open and would have scored {} ({} more)", chosen.name(), score, best.name(), best_score, best_score - score))
+    } else {
+        Some(format!("Coach: {} for {} was the best open choice, good call", chosen.name(), score))
+    }
+}
+
+fn all_scores_panel(scores: &[(ScoreType, u8, Option<u8>)]) -> Vec<String> {
+    let mut lines = vec!["ALL SCORES FOR THESE DICE   (Tn = used on turn n)".to_string()];
+
+    let rows = scores.len().div_ceil(2);
+
+    for row in 0..rows {
+        let mut line = String::new();
+        for (score_type, score, used) in scores.iter().skip(row).step_by(rows) {
+            let used = used.map_or("".to_string(), |turn| format!("(T{})", turn));
+            line.push_str(&format!("{:<16}{:>3} {:<6}  ", score_type.name(), score, used));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_reopens_a_scored_category() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        let roll = game.current_roll.clone();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert!(game.player().score_table.check_table(&ScoreType::Chance));
+
+        game.attempt_command(&Command::Undo).unwrap();
+        assert!(!game.player().score_table.check_table(&ScoreType::Chance));
+        assert!(game.current_roll == roll);
+    }
+
+    #[test]
+    fn refused_moves_take_no_undo_slot() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        assert_eq!(game.attempt_command(&Command::Roll), Err(CommandError::NoRollsLeft));
+        assert_eq!(game.history.len(), 2);
+    }
+
+    fn confirming_game() -> Game {
+        let mut game = Game::new(&GameOptions { seed: Some(7), confirm_zero: true, ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![1, 1, 2, 3, 4], 6).unwrap();
+        game
+    }
+
+    #[test]
+    fn confirmed_zero_is_scored() {
+        let mut game = confirming_game();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        assert_eq!(game.pending_score, Some(ScoreType::Yacht));
+        assert!(!game.player().score_table.check_table(&ScoreType::Yacht));
+
+        game.attempt_command(&Command::Confirm).unwrap();
+        assert_eq!(game.pending_score, None);
+        assert_eq!(game.player().score_table.table.get(&ScoreType::Yacht), Some(&0));
+    }
+
+    #[test]
+    fn cancelled_zero_is_not_scored() {
+        let mut game = confirming_game();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        game.attempt_command(&Command::Cancel).unwrap();
+        assert_eq!(game.pending_score, None);
+        assert!(!game.player().score_table.check_table(&ScoreType::Yacht));
+        assert!(game.current_roll == Roll::try_from_dice(vec![1, 1, 2, 3, 4], 6).unwrap());
+    }
+
+    fn two_player_game() -> Game {
+        Game::new(&GameOptions { seed: Some(7), players: vec!["Ann".to_string(), "Ben".to_string()], ..GameOptions::default() })
+    }
+
+    #[test]
+    fn turns_rotate_after_each_score() {
+        let mut game = two_player_game();
+        assert_eq!(game.player().name, "Ann");
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert_eq!(game.player().name, "Ben");
+        assert_eq!(game.rolls_used, 1);
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert_eq!(game.player().name, "Ann");
+        assert!(game.players.iter().all(|player| player.score_table.check_table(&ScoreType::Chance)));
+    }
+
+    #[test]
+    fn tied_game_goes_to_the_first_to_finish() {
+        let mut game = two_player_game();
+        let mut msg = String::new();
+        // both players score the same dice in the same categories
+        for score_type in Rules::default().categories() {
+            game.current_roll = Roll::try_from_dice(vec![1, 1, 2, 3, 4], 6).unwrap();
+            game.attempt_command(&Command::Score(score_type)).unwrap();
+            game.current_roll = Roll::try_from_dice(vec![1, 1, 2, 3, 4], 6).unwrap();
+            msg = game.attempt_command(&Command::Score(score_type)).unwrap();
+        }
+
+        assert_eq!(game.game_state, GameStates::GameOver);
+        assert_eq!(game.final_scores(), vec![("Ann".to_string(), 22), ("Ben".to_string(), 22)]);
+        assert!(msg.starts_with("Game Over! Ann wins!"), "{}", msg);
+        // the leaderboard is written by the terminal loop, not the game
+        assert!(game.record_pending);
+    }
+
+    #[test]
+    fn tie_break_prefers_fewer_rolls() {
+        let mut ann = Player::new("Ann");
+        let mut ben = Player::new("Ben");
+        for player in [&mut ann, &mut ben] {
+            player.score_table.table.insert(ScoreType::Chance, 20);
+        }
+        ann.rolls_taken = 30;
+        ben.rolls_taken = 25;
+        ann.finished_at = Some(1);
+        ben.finished_at = Some(2);
+
+        let ranked = rank_players(&[ann, ben]);
+        assert_eq!(ranked[0].name, "Ben");
+        assert_eq!(ranked[0].total, ranked[1].total);
+    }
+
+    #[test]
+    fn tie_breaks_apply_in_order() {
+        let summary = |name: &str, upper_bonus, rolls_taken, finished_at| PlayerSummary { name: name.to_string(), total: 200, upper_bonus, rolls_taken, finished_at };
+        let mut summaries = vec![
+            summary("late", false, 20, 4),
+            summary("slow", false, 30, 1),
+            summary("bonus", true, 39, 3),
+            summary("early", false, 20, 2),
+            PlayerSummary { total: 201, ..summary("top", false, 39, 5) },
+        ];
+        summaries.sort_by(compare_summaries);
+
+        let names: Vec<&str> = summaries.iter().map(|summary| summary.name.as_str()).collect();
+        assert_eq!(names, ["top", "bonus", "early", "late", "slow"]);
+
+        let panel = standings_panel(&summaries);
+        assert_eq!(panel[1], "🥇   top              201  (39 rolls)");
+        assert_eq!(panel[2], "🥈   bonus            200  (bonus, 39 rolls)");
+        assert!(panel[4].starts_with("4. "));
+    }
+
+    #[test]
+    fn chase_goes_after_the_face_most_needed() {
+        let mut game = Game::new(&GameOptions::default());
+        game.current_roll = Roll::try_from_dice(vec![6, 2, 2, 2, 2], 6).unwrap();
+        // four twos are already more than the bonus needs from Twos, one six is a long way short for Sixes
+        assert_eq!(game.bonus_target(), Some(6));
+
+        game.attempt_command(&Command::ChaseBonus).unwrap();
+        assert_eq!(game.current_roll.holds, [true, false, false, false, false]);
+        assert_eq!(game.current_roll.dice[0], 6);
+    }
+
+    #[test]
+    fn chase_skips_filled_and_missing_faces() {
+        let mut game = Game::new(&GameOptions::default());
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 1, 3], 6).unwrap();
+        game.players[0].score_table.table.insert(ScoreType::Sixes, 24);
+        assert_eq!(game.bonus_target(), Some(3));
+
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 6, 6], 6).unwrap();
+        assert_eq!(game.bonus_target(), Some(5));
+    }
+
+    #[test]
+    fn five_roll_turn() {
+        let mut game = Game::new(&GameOptions { rolls_per_turn: 5, ..GameOptions::default() });
+        for rolls_used in 2..=5 {
+            game.attempt_command(&Command::Roll).unwrap();
+            assert_eq!(game.rolls_used, rolls_used);
+        }
+        assert_eq!(game.attempt_command(&Command::Roll), Err(CommandError::NoRollsLeft));
+        assert_eq!(game.rolls_used, 5);
+
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert_eq!(game.rolls_used, 1);
+    }
+
+    #[test]
+    fn two_and_four_roll_turns() {
+        for rolls_per_turn in [2, 4] {
+            let mut game = Game::new(&GameOptions { seed: Some(7), rolls_per_turn, ..GameOptions::default() });
+            for _ in 1..rolls_per_turn {
+                assert!(!game.is_final_roll());
+                game.attempt_command(&Command::Roll).unwrap();
+            }
+            assert!(game.is_final_roll());
+            assert_eq!(game.state_label(), format!("Rolling({}/{})", rolls_per_turn, rolls_per_turn));
+            assert_eq!(game.attempt_command(&Command::Roll), Err(CommandError::NoRollsLeft));
+
+            game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+            assert_eq!(game.rolls_left(), rolls_per_turn - 1);
+        }
+    }
+
+    #[test]
+    fn lucky_bonus_needs_three_of_the_face() {
+        let mut game = Game::new(&GameOptions { lucky: true, ..GameOptions::default() });
+        game.lucky_face = Some(4);
+
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 1, 4, 2], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert_eq!(game.player().lucky_bonus, LUCKY_BONUS);
+        assert_eq!(game.total(), 15 + LUCKY_BONUS);
+
+        // two of the face isn't enough
+        game.lucky_face = Some(4);
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 1, 3, 2], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Fours)).unwrap();
+        assert_eq!(game.player().lucky_bonus, LUCKY_BONUS);
+
+        // scratching never earns it
+        game.lucky_face = Some(4);
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 2], 6).unwrap();
+        game.attempt_command(&Command::Scratch(ScoreType::Yacht)).unwrap();
+        assert_eq!(game.player().lucky_bonus, LUCKY_BONUS);
+    }
+
+    #[test]
+    fn status_line_at_a_known_state() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        assert_eq!(game.status_line(), "Seed 7 · Turn 1/13 · Rolls left 2");
+
+        game.attempt_command(&Command::Roll).unwrap();
+        assert_eq!(game.status_line(), "Seed 7 · Turn 1/13 · Rolls left 1");
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert_eq!(game.status_line(), "Seed 7 · Turn 2/13 · Rolls left 2");
+    }
+
+    #[test]
+    fn categories_remember_the_turn_they_were_used() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Aces)).unwrap();
+
+        let turn_filled = &game.player().score_table.turn_filled;
+        assert_eq!(turn_filled.get(&ScoreType::Chance), Some(&1));
+        assert_eq!(turn_filled.get(&ScoreType::Yacht), Some(&2));
+        assert_eq!(turn_filled.get(&ScoreType::Aces), Some(&3));
+        assert_eq!(turn_filled.len(), 3);
+
+        // a refused score doesn't count as using the category again
+        assert_eq!(game.attempt_command(&Command::Score(ScoreType::Chance)), Err(CommandError::AlreadyScored));
+        assert_eq!(game.player().score_table.turn_filled.get(&ScoreType::Chance), Some(&1));
+    }
+
+    #[test]
+    fn hints_fade_as_the_average_rises() {
+        let level = |games_played, total_points| SessionStats { games_played, total_points }.hint_level();
+        assert_eq!(level(0, 0), HintLevel::Full);
+        assert_eq!(level(2, 298), HintLevel::Full);
+        assert_eq!(level(2, 300), HintLevel::Brief);
+        assert_eq!(level(3, 599), HintLevel::Brief);
+        assert_eq!(level(3, 600), HintLevel::Off);
+
+        // a level asked for by name holds whatever the average
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.session = SessionStats { games_played: 1, total_points: 250 };
+        assert_eq!(game.hint_level(), HintLevel::Off);
+        game.attempt_command(&Command::Hints(Some(HintLevel::Full))).unwrap();
+        assert_eq!(game.hint_level(), HintLevel::Full);
+    }
+
+    #[test]
+    fn drill_counts_attempts_and_hits() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), drill: Some(ScoreType::Yacht), ..GameOptions::default() });
+
+        game.attempt_command(&Command::Roll).unwrap();
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        assert_eq!(game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap(), "Hit! 1 hits in 1 attempts (100%), 1.0 attempts and 2.0 rolls per hit");
+        assert_eq!(game.rolls_used, 1);
+
+        game.current_roll = Roll::try_from_dice(vec![1, 1, 2, 3, 4], 6).unwrap();
+        assert_eq!(game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap(), "Missed. 1 hits in 2 attempts (50%), 2.0 attempts and 2.0 rolls per hit");
+
+        // a scratch is a miss whatever the dice, and the table is never touched
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 6, 6], 6).unwrap();
+        assert!(game.attempt_command(&Command::Scratch(ScoreType::Yacht)).unwrap().starts_with("Missed. 1 hits in 3 attempts (33%)"));
+        assert!(game.player().score_table.table.is_empty());
+    }
+
+    #[test]
+    fn transitions_are_logged_in_order() {
+        let path = std::env::temp_dir().join(format!("yacht_test_{}_transitions.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.transition_log = Some(TransitionLog { path: path.to_string_lossy().into_owned() });
+
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+
+        // each line starts with its timestamp, which is left out here
+        let log = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = log.lines().map(|line| line.split_once(' ').unwrap().1).collect();
+        assert_eq!(lines, [
+            "Rolling(1/3) -> Rolling(2/3) on Roll",
+            "Rolling(2/3) -> Rolling(3/3) on Roll",
+            "Rolling(3/3) -> Rolling(1/3) on Score(Chance)",
+        ]);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn dice_rolled_are_tallied_by_face() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        // tallied alongside the game, counting only the dice each roll actually threw
+        let mut expected = [0; 7];
+        let mut tally = |dice: &[u8], holds: &[bool]| {
+            for (&face, &held) in dice.iter().zip(holds) {
+                if !held {
+                    expected[face as usize] += 1;
+                }
+            }
+        };
+        tally(&game.current_roll.dice, &[false; 5]);
+
+        game.attempt_command(&Command::HoldMany(vec![DiceNum::First], vec![])).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        tally(&game.current_roll.dice, &[true, false, false, false, false]);
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        tally(&game.current_roll.dice, &[false; 5]);
+        game.attempt_command(&Command::Roll).unwrap();
+        tally(&game.current_roll.dice, &[false; 5]);
+
+        assert_eq!(game.faces_rolled, expected);
+        assert_eq!(game.faces_rolled.iter().sum::<u32>(), 19);
+        assert_eq!(dice_histogram_panel(&game.faces_rolled)[0], "DICE ROLLED THIS GAME   (19 in all)");
+
+        game.attempt_command(&Command::New).unwrap();
+        assert_eq!(game.faces_rolled.iter().sum::<u32>(), 5);
+    }
+
+    #[test]
+    fn coach_critiques_a_weaker_choice() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), coach: true, ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 6, 6], 6).unwrap();
+        let msg = game.attempt_command(&Command::Score(ScoreType::Sixes)).unwrap();
+        assert!(msg.ends_with(" Coach: Sixes scored 30, but Yacht was open and would have scored 50 (20 more)"), "{}", msg);
+
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 6, 6], 6).unwrap();
+        let msg = game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        assert!(msg.ends_with(" Coach: Yacht for 50 was the best open choice, good call"), "{}", msg);
+
+        // nothing is said with coach mode off
+        game.attempt_command(&Command::Coach).unwrap();
+        let msg = game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert!(!msg.contains("Coach:"), "{}", msg);
+    }
+
+    #[test]
+    fn replayed_choices_on_other_dice() {
+        let rules = Rules::default();
+        let choices = rules.categories();
+        // every category at its best
+        let most: u16 = choices.iter().map(|score_type| score_type.max_score(&rules) as u16).sum();
+
+        let first = replay_choices(&choices, 1, 3, &rules);
+        let second = replay_choices(&choices, 2, 3, &rules);
+        assert_ne!(first, second);
+        assert!(first <= most && second <= most);
+        assert_eq!(replay_choices(&choices, 1, 3, &rules), first);
+
+        // a finished game replays the order its categories were filled in
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        for score_type in rules.categories().into_iter().rev() {
+            game.attempt_command(&Command::Score(score_type)).unwrap();
+        }
+        game.attempt_command(&Command::Replay(Some(1))).unwrap();
+        let expected = replay_choices(&rules.categories().into_iter().rev().collect::<Vec<_>>(), 1, 3, &rules);
+        assert_eq!(game.panel[0], "YOUR CHOICES REPLAYED ON SEED 1");
+        assert!(game.panel[1].contains(&format!("replayed {:>4}", expected)), "{}", game.panel[1]);
+    }
+
+    #[test]
+    fn new_game_clears_the_holds() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        for num in [DiceNum::First, DiceNum::Third, DiceNum::Fourth] {
+            game.attempt_command(&Command::HoldMany(vec![num], vec![])).unwrap();
+        }
+        assert_eq!(game.current_roll.holds.iter().filter(|&&held| held).count(), 3);
+
+        game.attempt_command(&Command::New).unwrap();
+        assert_eq!(game.current_roll.holds, [false; 5]);
+        assert_eq!(game.rolls_used, 1);
+    }
+
+    #[test]
+    fn three_of_a_kind_scores_and_ends_the_card() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![2, 5, 2, 6, 2], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::ThreeOfKind)).unwrap();
+        assert_eq!(game.player().score_table.table.get(&ScoreType::ThreeOfKind), Some(&17));
+
+        // the game only ends once every one of the 13 categories is used
+        let rest: Vec<ScoreType> = Rules::default().categories().into_iter().filter(|&score_type| score_type != ScoreType::ThreeOfKind).collect();
+        for score_type in &rest[..11] {
+            game.attempt_command(&Command::Score(*score_type)).unwrap();
+        }
+        assert_eq!(game.game_state, GameStates::Rolling);
+        game.attempt_command(&Command::Score(rest[11])).unwrap();
+        assert_eq!(game.game_state, GameStates::GameOver);
+        assert_eq!(game.player().score_table.table.len(), 13);
+    }
+
+    #[test]
+    fn finished_card_can_be_looked_over_but_not_changed() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        for score_type in game.rules.categories() {
+            game.attempt_command(&Command::Scratch(score_type)).unwrap();
+        }
+        assert_eq!(game.game_state, GameStates::GameOver);
+        let card = game.player().score_table.clone();
+
+        assert!(game.attempt_command(&Command::Stats).is_ok());
+        assert!(game.attempt_command(&Command::Info(Some(ScoreType::Yacht))).is_ok());
+        for command in [Command::HoldMany(vec![DiceNum::First], vec![]), Command::Sort, Command::Scratch(ScoreType::Yacht), Command::Undo] {
+            assert_eq!(game.attempt_command(&command), Err(CommandError::GameOver), "{:?}", command);
+        }
+        assert!(game.player().score_table == card);
+        assert_eq!(game.game_state, GameStates::GameOver);
+
+        game.attempt_command(&Command::New).unwrap();
+        assert_eq!(game.game_state, GameStates::Rolling);
+    }
+
+    #[test]
+    fn saved_game_loads_back_the_same() {
+        let path = std::env::temp_dir().join(format!("yacht_test_{}_save.json", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::HoldMany(vec![DiceNum::Second], vec![])).unwrap();
+        fs::write(&path, game.to_json().to_string()).unwrap();
+
+        let loaded = Game::load(&path, &GameOptions::default());
+        let _ = fs::remove_file(&path);
+        let Ok(loaded) = loaded else { panic!("the save didn't load") };
+        assert_eq!(loaded.player().score_table.table, game.player().score_table.table);
+        assert_eq!(loaded.current_roll.dice, game.current_roll.dice);
+        assert_eq!(loaded.current_roll.holds, game.current_roll.holds);
+        assert_eq!(loaded.rolls_used, 2);
+        assert!(loaded.game_state == GameStates::Rolling);
+    }
+
+    #[test]
+    fn missing_or_corrupt_saves_are_errors() {
+        let path = std::env::temp_dir().join(format!("yacht_test_{}_corrupt.json", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = fs::remove_file(&path);
+        assert!(matches!(Game::load(&path, &GameOptions::default()), Err(e) if e.starts_with("Couldn't read")));
+
+        fs::write(&path, "{\"score_table\": [").unwrap();
+        let loaded = Game::load(&path, &GameOptions::default());
+        let _ = fs::remove_file(&path);
+        assert!(matches!(loaded, Err(e) if e.contains("is not a valid save")));
+    }
+
+    #[test]
+    fn preview_covers_only_open_categories() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        game.current_roll = Roll::try_from_dice(vec![3, 3, 3, 5, 5], 6).unwrap();
+        let rolls_used = game.rolls_used;
+
+        let preview = game.preview_scores();
+        assert_eq!(preview.len(), 12);
+        assert!(!preview.contains_key(&ScoreType::Chance));
+        assert_eq!(preview[&ScoreType::Threes], 9);
+        assert_eq!(preview[&ScoreType::Fives], 10);
+        assert_eq!(preview[&ScoreType::FullHouse], 25);
+        assert_eq!(preview[&ScoreType::ThreeOfKind], 19);
+        assert_eq!(preview[&ScoreType::Yacht], 0);
+
+        // looking doesn't use a roll or change the dice
+        game.attempt_command(&Command::Preview).unwrap();
+        assert_eq!(game.rolls_used, rolls_used);
+        assert_eq!(game.current_roll.dice, [3, 3, 3, 5, 5]);
+    }
+
+    #[test]
+    fn preview_follows_the_hint_level() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![3, 3, 3, 5, 5], 6).unwrap();
+        assert_eq!(game.preview_scores().len(), 13);
+
+        // brief hints only give away the best open category
+        game.attempt_command(&Command::Hints(Some(HintLevel::Brief))).unwrap();
+        assert_eq!(game.preview_scores(), HashMap::from([(ScoreType::FullHouse, 25)]));
+
+        game.attempt_command(&Command::Hints(Some(HintLevel::Off))).unwrap();
+        assert_eq!(game.attempt_command(&Command::Preview), Err(CommandError::HintsOff));
+        assert!(game.preview_scores().is_empty());
+    }
+
+    #[test]
+    fn ai_choices_for_known_dice() {
+        let rules = Rules::default();
+        let empty = ScoreTable::new();
+        let choose = |dice, table: &ScoreTable, rolls_left| ai_choose(&Roll::try_from_dice(dice, 6).unwrap(), table, rolls_left, &rules);
+
+        // a straight is worth taking, a pair is worth rolling for
+        assert_eq!(choose(vec![2, 3, 4, 5, 6], &empty, 2), AiMove::Score(ScoreType::BigStraight));
+        assert_eq!(choose(vec![1, 5, 3, 5, 2], &empty, 2), AiMove::Reroll(vec![false, true, false, true, false]));
+        // ties keep the higher face
+        assert_eq!(choose(vec![2, 2, 6, 6, 1], &empty, 1), AiMove::Reroll(vec![false, false, true, true, false]));
+        // out of rolls, the best open category it is
+        assert_eq!(choose(vec![1, 5, 3, 5, 2], &empty, 0), AiMove::Score(ScoreType::Chance));
+
+        // five of a kind is scored even with Yacht used, there's nothing left to roll
+        let mut table = ScoreTable::new();
+        table.table.insert(ScoreType::Yacht, 50);
+        assert!(matches!(choose(vec![1, 1, 1, 1, 1], &table, 2), AiMove::Score(_)));
+    }
+
+    #[test]
+    fn second_yacht_adds_a_bonus() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        assert_eq!(game.player().score_table.table.get(&ScoreType::Yacht), Some(&50));
+        assert_eq!(game.player().score_table.yacht_bonus, 0);
+
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Fours)).unwrap();
+        assert_eq!(game.player().score_table.yacht_bonus, 100);
+        assert_eq!(game.player().score_table.table_total(), 50 + 20 + 100);
+    }
+
+    #[test]
+    fn no_yacht_bonus_after_a_scratched_yacht() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Scratch(ScoreType::Yacht)).unwrap();
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Fours)).unwrap();
+        assert_eq!(game.player().score_table.yacht_bonus, 0);
+    }
+
+    #[test]
+    fn joker_goes_to_the_lower_section_once_its_upper_box_is_filled() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.players[0].score_table.table.insert(ScoreType::Fours, 0);
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        let yachts = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        let lower: Vec<ScoreType> = Rules::default().categories().into_iter().filter(|score_type| score_type.face().is_none() && *score_type != ScoreType::Yacht).collect();
+        assert_eq!(game.joker_allowed_categories(&yachts), Some(lower.clone()));
+
+        game.current_roll = yachts.clone();
+        assert_eq!(game.attempt_command(&Command::Score(ScoreType::Aces)), Err(CommandError::JokerElsewhere(lower)));
+        game.attempt_command(&Command::Score(ScoreType::LittleStraight)).unwrap();
+        assert_eq!(game.player().score_table.table.get(&ScoreType::LittleStraight), Some(&30));
+    }
+
+    #[test]
+    fn joker_goes_anywhere_once_the_lower_section_is_full() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        for score_type in ScoreType::all().into_iter().filter(|score_type| score_type.face().is_none() || *score_type == ScoreType::Fours) {
+            game.players[0].score_table.table.insert(score_type, 0);
+        }
+        // the Yacht box only needs to be filled, even with a 0, for jokers to apply
+        assert_eq!(game.joker_allowed_categories(&Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap()), Some(vec![ScoreType::Aces, ScoreType::Twos, ScoreType::Threes, ScoreType::Fives, ScoreType::Sixes]));
+        assert_eq!(game.joker_allowed_categories(&Roll::try_from_dice(vec![4, 4, 4, 4, 2], 6).unwrap()), None);
+    }
+
+    #[test]
+    fn stats_count_a_scripted_game() {
+        let mut game = two_player_game();
+        // the first roll of the game is dealt, so it counts already
+        assert_eq!((game.stats.rolls, game.stats.dice_rolled), (1, 5));
+
+        game.attempt_command(&Command::HoldMany(vec![DiceNum::First], vec![])).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 2, 2], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Sixes)).unwrap();
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 1, 2, 2], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Sixes)).unwrap();
+        game.current_roll = Roll::try_from_dice(vec![1, 2, 3, 4, 6], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+
+        // a roll with one die held throws four, each new turn deals five
+        assert_eq!((game.stats.rolls, game.stats.dice_rolled), (5, 24));
+        assert_eq!(game.stats.average(&ScoreType::Sixes), Some(15.0));
+        assert_eq!(game.stats.average(&ScoreType::Yacht), Some(0.0));
+        assert_eq!(game.stats.average(&ScoreType::Chance), None);
+        assert_eq!(game.stats.zero_turns, 1);
+        assert_eq!(game.stats.summary(), "5 rolls, 24 dice, 1 turn scored 0");
+    }
+
+    #[test]
+    fn exported_scorecard_reads_back() {
+        let path = std::env::temp_dir().join(format!("yacht_test_{}_export.json", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = fs::remove_file(&path);
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        assert_eq!(game.attempt_command(&Command::Export(path.clone())), Err(CommandError::ExportTooEarly));
+
+        for score_type in Rules::default().categories() {
+            game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 2, 2], 6).unwrap();
+            game.attempt_command(&Command::Score(score_type)).unwrap();
+        }
+        game.attempt_command(&Command::Export(path.clone())).unwrap();
+        let contents = fs::read_to_string(&path);
+        let _ = fs::remove_file(&path);
+
+        let export = json::parse(&contents.unwrap()).unwrap();
+        let card = &export.get("players").and_then(Json::as_array).unwrap()[0];
+        let categories = card.get("categories").unwrap();
+        let field = |json: &Json, key: &str| json.get(key).and_then(Json::as_u64);
+        assert_eq!(export.get("seed").and_then(Json::as_u64), Some(7));
+        assert_eq!(card.get("name").and_then(Json::as_str), Some("Player"));
+        assert_eq!(field(categories, "sixes"), Some(18));
+        assert_eq!(field(categories, "fullhouse"), Some(25));
+        assert_eq!(field(categories, "yacht"), Some(0));
+        assert_eq!(field(card, "upper_total"), Some(22));
+        assert_eq!(field(card, "upper_bonus"), Some(0));
+        assert_eq!(field(card, "card_total"), Some(91));
+        assert_eq!(field(card, "grand_total"), Some(91));
+    }
+
+    #[test]
+    fn same_seed_rolls_the_same_dice() {
+        let rolls = |seed| {
+            let mut game = Game::new(&GameOptions { seed: Some(seed), ..GameOptions::default() });
+            let mut dice = vec![game.current_roll.dice.clone()];
+            game.attempt_command(&Command::HoldMany(vec![DiceNum::Second], vec![])).unwrap();
+            game.attempt_command(&Command::Roll).unwrap();
+            dice.push(game.current_roll.dice.clone());
+            game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+            for _ in 0..2 {
+                dice.push(game.current_roll.dice.clone());
+                game.attempt_command(&Command::Roll).unwrap();
+            }
+            dice
+        };
+
+        assert_eq!(rolls(42), rolls(42));
+        assert_ne!(rolls(42), rolls(43));
+    }
+
+    #[test]
+    fn daily_seed_from_the_date() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(19_753), (2024, 1, 31));
+        assert_eq!(daily_seed(2024, 1, 31), 20_240_131);
+        assert_eq!(daily_seed(1999, 12, 31), 19_991_231);
+    }
+
+    #[test]
+    fn scratch_takes_a_zero_only_in_an_open_category() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 6, 5], 6).unwrap();
+        game.attempt_command(&Command::Scratch(ScoreType::Sixes)).unwrap();
+        assert_eq!(game.player().score_table.table.get(&ScoreType::Sixes), Some(&0));
+
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        let chance = game.player().score_table.table[&ScoreType::Chance];
+        assert_ne!(chance, 0);
+        assert_eq!(game.attempt_command(&Command::Scratch(ScoreType::Chance)), Err(CommandError::AlreadyScored));
+        assert_eq!(game.player().score_table.table[&ScoreType::Chance], chance);
+    }
+
+    #[test]
+    fn roll_first_refuses_scoring_the_dealt_dice() {
+        let rules = Rules { roll_first: true, ..Rules::default() };
+        let mut game = Game::new(&GameOptions { seed: Some(7), rules, ..GameOptions::default() });
+        let refused = Err(CommandError::MustRollFirst);
+        assert_eq!(game.attempt_command(&Command::Score(ScoreType::Chance)), refused);
+        assert!(game.player().score_table.table.is_empty());
+
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        // and again on the next turn
+        assert_eq!(game.attempt_command(&Command::Score(ScoreType::Sixes)), refused);
+    }
+
+    #[test]
+    fn timeout_gives_up_the_least_valuable_open_category() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        assert_eq!(game.timeout_scratch(), Some(ScoreType::Aces));
+        game.attempt_command(&Command::Scratch(ScoreType::Aces)).unwrap();
+        assert_eq!(game.timeout_scratch(), Some(ScoreType::Twos));
+
+        // the straights, Chance and Three Of A Kind are all worth 30 at best, the first of them goes
+        for score_type in [ScoreType::Twos, ScoreType::Threes, ScoreType::Fours, ScoreType::Fives, ScoreType::Sixes, ScoreType::FourOfKind, ScoreType::FullHouse] {
+            game.attempt_command(&Command::Scratch(score_type)).unwrap();
+        }
+        assert_eq!(game.timeout_scratch(), Some(ScoreType::LittleStraight));
+    }
+
+    #[test]
+    fn timeout_with_a_joker_keeps_to_the_joker_boxes() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Scratch(ScoreType::Yacht)).unwrap();
+        // Aces is worth less, but a yacht of 3s has to go in Threes
+        game.current_roll = Roll::try_from_dice(vec![3, 3, 3, 3, 3], 6).unwrap();
+        assert_eq!(game.timeout_scratch(), Some(ScoreType::Threes));
+    }
+
+    #[test]
+    fn game_ends_when_the_active_categories_are_full() {
+        for ruleset in [Ruleset::Yacht, Ruleset::Generala] {
+            let mut game = Game::new(&GameOptions { seed: Some(7), rules: Rules { ruleset, ..Rules::default() }, ..GameOptions::default() });
+            let categories = game.rules.categories();
+            let (last, rest) = categories.split_last().unwrap();
+            for score_type in rest {
+                game.attempt_command(&Command::Scratch(*score_type)).unwrap();
+            }
+            assert_eq!(game.game_state, GameStates::Rolling, "{:?}", ruleset);
+
+            game.attempt_command(&Command::Scratch(*last)).unwrap();
+            assert_eq!(game.game_state, GameStates::GameOver, "{:?}", ruleset);
+        }
+    }
+
+    #[test]
+    fn roll_with_every_die_held_is_refused() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Roll).unwrap();
+        for num in 1..=5 {
+            game.attempt_command(&Command::HoldMany(vec![DiceNum::from_number(num, 5).unwrap()], vec![])).unwrap();
+        }
+        let (dice, rolls_left) = (game.current_roll.dice.clone(), game.rolls_left());
+
+        assert_eq!(game.attempt_command(&Command::Roll), Err(CommandError::AllHeld));
+        assert_eq!(game.current_roll.dice, dice);
+        assert_eq!(game.rolls_left(), rolls_left);
+
+        // letting the dice go makes the roll count again
+        game.attempt_command(&Command::ClearHolds).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        assert_eq!(game.rolls_left(), rolls_left - 1);
+    }
+
+    #[test]
+    fn refusals_come_back_as_their_own_error() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        assert_eq!(game.attempt_command(&Command::Undo), Err(CommandError::NothingToUndo));
+        assert_eq!(game.attempt_command(&Command::Confirm), Err(CommandError::NothingToConfirm));
+        assert_eq!(game.attempt_command(&Command::Cancel), Err(CommandError::NothingToConfirm));
+        assert_eq!(game.attempt_command(&Command::Replay(Some(1))), Err(CommandError::ReplayTooEarly));
+
+        game.attempt_command(&Command::Hints(Some(HintLevel::Off))).unwrap();
+        for command in [Command::ChaseBonus, Command::BonusOdds, Command::Odds(ScoreType::Yacht), Command::AllScores, Command::Preview] {
+            assert_eq!(game.attempt_command(&command), Err(CommandError::HintsOff), "{:?}", command);
+        }
+        game.attempt_command(&Command::Hints(Some(HintLevel::Full))).unwrap();
+
+        // 14 points short with every upper box used, then over the bonus line
+        let upper = [ScoreType::Aces, ScoreType::Twos, ScoreType::Threes, ScoreType::Fours, ScoreType::Fives, ScoreType::Sixes];
+        for (score_type, points) in upper.into_iter().zip([1, 4, 6, 8, 10, 20]) {
+            game.players[0].score_table.table.insert(score_type, points);
+        }
+        assert_eq!(game.attempt_command(&Command::ChaseBonus), Err(CommandError::NoUpperOpen));
+
+        game.players[0].score_table.table.insert(ScoreType::Fives, 25);
+        assert_eq!(game.attempt_command(&Command::ChaseBonus), Err(CommandError::BonusReached));
+        assert_eq!(game.attempt_command(&Command::BonusOdds), Err(CommandError::BonusReached));
+    }
+
+    #[test]
+    fn six_dice_hold_and_roll_together() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), rules: Rules { dice: 6, ..Rules::default() }, ..GameOptions::default() });
+        assert_eq!(game.current_roll.dice.len(), 6);
+        assert!(!game.rules.categories().contains(&ScoreType::FullHouse));
+
+        game.attempt_command(&Command::Roll).unwrap();
+        for num in [1, 6] {
+            game.attempt_command(&Command::HoldMany(vec![DiceNum::from_number(num, 6).unwrap()], vec![])).unwrap();
+        }
+        assert_eq!(game.current_roll.holds, vec![true, false, false, false, false, true]);
+
+        // the sixth die keeps its face with the first, and the ones between are rolled again
+        let before = game.current_roll.dice.clone();
+        game.attempt_command(&Command::Roll).unwrap();
+        assert_eq!(game.current_roll.dice.len(), 6);
+        assert_eq!((game.current_roll.dice[0], game.current_roll.dice[5]), (before[0], before[5]));
+        assert!(game.current_roll.dice.iter().all(|&die| (1..=6).contains(&die)));
+        assert_eq!(game.current_roll.holds, vec![true, false, false, false, false, true]);
+    }
+
+    #[test]
+    fn better_open_category_asks_first() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), confirm_better: true, ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert_eq!(game.pending_score, Some(ScoreType::Chance));
+        assert!(!game.player().score_table.check_table(&ScoreType::Chance));
+        game.attempt_command(&Command::Cancel).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        assert_eq!(game.pending_score, None);
+        assert_eq!(game.player().score_table.table.get(&ScoreType::Yacht), Some(&50));
+
+        // Three of a Kind only ties Chance's 28, so it goes straight in, while Full House's 25 is questioned
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 5, 5], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::FullHouse)).unwrap();
+        assert_eq!(game.pending_score, Some(ScoreType::FullHouse));
+        game.attempt_command(&Command::Confirm).unwrap();
+        assert_eq!(game.player().score_table.table.get(&ScoreType::FullHouse), Some(&25));
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 5, 5], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::ThreeOfKind)).unwrap();
+        assert_eq!(game.pending_score, None);
+        assert_eq!(game.player().score_table.table.get(&ScoreType::ThreeOfKind), Some(&28));
+
+        // without the flag the score goes straight in
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![4, 4, 4, 4, 4], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert_eq!(game.player().score_table.table.get(&ScoreType::Chance), Some(&20));
+    }
+
+    #[test]
+    fn turn_counts_up_to_the_last_round() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        assert_eq!(game.turn(), 1);
+
+        let categories = game.rules.categories();
+        for (i, score_type) in categories.iter().enumerate().take(6) {
+            game.players[0].score_table.table.insert(*score_type, i as u8);
+        }
+        assert_eq!(game.turn(), 7);
+
+        for score_type in &categories[6..12] {
+            game.players[0].score_table.table.insert(*score_type, 0);
+        }
+        assert_eq!(game.turn(), categories.len());
+        // a full card stays on the last round rather than counting past it
+        game.players[0].score_table.table.insert(categories[12], 0);
+        assert_eq!(game.turn(), categories.len());
+
+        let generala = Game::new(&GameOptions { seed: Some(7), rules: Rules { ruleset: Ruleset::Generala, ..Rules::default() }, ..GameOptions::default() });
+        assert!(generala.status_line().contains(&format!("Turn 1/{}", generala.rules.categories().len())), "{}", generala.status_line());
+    }
+
+    #[test]
+    fn cloned_game_is_equal_until_it_moves() {
+        let mut game = two_player_game();
+        for command in [Command::Roll, Command::Score(ScoreType::Chance), Command::Roll] {
+            game.attempt_command(&command).unwrap();
+        }
+
+        let mut copy = game.clone();
+        assert!(copy == game);
+        copy.attempt_command(&Command::HoldMany(vec![DiceNum::from_number(1, 5).unwrap()], vec![])).unwrap();
+        assert!(copy != game);
+
+        // the copy carries on from the same rng, so the same move makes the same game
+        let mut copy = game.clone();
+        copy.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::Roll).unwrap();
+        assert!(copy == game);
+    }
+
+    #[test]
+    fn want_highlights_without_holding() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![6, 2, 6, 4, 6], 6).unwrap();
+        assert_eq!(game.attempt_command(&Command::Want(ScoreType::Sixes)), Ok("Sixes wants the 3 highlighted dice held, it would score 18 now".to_string()));
+        assert_eq!(game.highlight, Some(ScoreType::Sixes));
+        assert_eq!(game.current_roll.holds, vec![false; 5]);
+
+        game.current_roll = Roll::try_from_dice(vec![1, 2, 2, 4, 3], 6).unwrap();
+        assert_eq!(game.attempt_command(&Command::Want(ScoreType::LittleStraight)), Ok("Little Straight wants the 4 highlighted dice held, it would score 0 now".to_string()));
+        assert_eq!(game.current_roll.suggested_holds(&ScoreType::LittleStraight), vec![true, true, false, true, true]);
+        assert_eq!(game.current_roll.holds, vec![false; 5]);
+    }
+
+    #[test]
+    fn final_score_broken_down_by_section() {
+        let mut player = Player::new("Ann");
+        for (score_type, points) in [(ScoreType::Sixes, 30), (ScoreType::Fives, 25), (ScoreType::Fours, 12), (ScoreType::Chance, 24), (ScoreType::Yacht, 50)] {
+            player.score_table.table.insert(score_type, points);
+        }
+        player.score_table.yacht_bonus = 100;
+        player.lucky_bonus = 5;
+
+        assert_eq!(section_panel(&player), [
+            "FINAL SCORE   Upper 67  Upper bonus 35  Lower 74",
+            "              Yacht bonus 100  Lucky bonus 5  Total 281",
+        ]);
+    }
+
+    #[test]
+    fn match_tallies_wins_until_the_target() {
+        let mut match_play = MatchPlay::new(400, 2);
+        match_play.record(&[200, 180]);
+        assert_eq!((match_play.totals.clone(), match_play.wins.clone()), (vec![200, 180], vec![1, 0]));
+        assert_eq!(match_play.winner(), None);
+
+        // a drawn game is a win for both
+        match_play.record(&[150, 150]);
+        assert_eq!(match_play.wins, vec![2, 1]);
+        assert_eq!(match_play.winner(), None);
+
+        match_play.record(&[40, 90]);
+        assert_eq!((match_play.games, match_play.totals.clone()), (3, vec![390, 420]));
+        assert_eq!(match_play.winner(), Some(1));
+        assert_eq!(match_play.standing(&["Ann", "Ben"]), "Ann 390 (2 won), Ben 420 (2 won) of 400");
+
+        // level on points past the target, the games won decide it
+        let mut match_play = MatchPlay::new(100, 2);
+        match_play.record(&[101, 100]);
+        match_play.record(&[101, 100]);
+        match_play.record(&[0, 2]);
+        assert_eq!(match_play.totals, vec![202, 202]);
+        assert_eq!(match_play.winner(), Some(0));
+    }
+
+    #[test]
+    fn match_starts_the_next_game_until_the_target() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), match_target: Some(1000), ..GameOptions::default() });
+        for score_type in game.rules.categories() {
+            game.attempt_command(&Command::Roll).unwrap();
+            game.attempt_command(&Command::Score(score_type)).unwrap();
+        }
+        let match_play = game.match_play.clone().unwrap();
+        assert_eq!(match_play.games, 1);
+        assert!(match_play.totals[0] > 0 && match_play.totals[0] < 1000);
+        // a fresh card straight away, rather than game over
+        assert_eq!(game.game_state, GameStates::Rolling);
+        assert!(game.player().score_table.table.is_empty());
+    }
+
+    #[test]
+    fn reroll_rolls_only_the_named_dice() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::HoldMany(vec![DiceNum::from_number(5, 5).unwrap()], vec![])).unwrap();
+        let (before, rolls_left) = (game.current_roll.dice.clone(), game.rolls_left());
+
+        // the same dice as holding the other two and rolling
+        let mut held = game.clone();
+        held.attempt_command(&Command::HoldMany(vec![DiceNum::from_number(4, 5).unwrap()], vec![])).unwrap();
+        held.attempt_command(&Command::Roll).unwrap();
+
+        let reroll = Command::ReRoll(vec![DiceNum::First, DiceNum::Second, DiceNum::Third]);
+        assert_eq!(game.attempt_command(&reroll), Ok("Rerolled dice 1, 2, 3".to_string()));
+        assert_eq!(game.current_roll.dice, held.current_roll.dice);
+        assert_eq!(game.current_roll.dice[3..], before[3..]);
+        // the holds are left as they were, and the roll is used up
+        assert_eq!(game.current_roll.holds, vec![false, false, false, false, true]);
+        assert_eq!(game.rolls_left(), rolls_left - 1);
+        assert_eq!(game.rolls_left(), 0);
+        assert_eq!(game.attempt_command(&reroll), Err(CommandError::NoRollsLeft));
+    }
+
+    #[test]
+    fn bells_for_a_yacht_the_bonus_and_game_over() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![5, 5, 5, 5, 5], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Yacht)).unwrap();
+        assert_eq!(game.notices, [Notice::Yacht]);
+        game.notices.clear();
+
+        // five alike in Chance is no yacht, and 60 in the upper section is no bonus yet
+        for (score_type, points) in [(ScoreType::Aces, 3), (ScoreType::Twos, 6), (ScoreType::Threes, 9), (ScoreType::Fours, 12), (ScoreType::Fives, 15)] {
+            game.players[0].score_table.table.insert(score_type, points);
+        }
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 5, 1, 2], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert!(game.notices.is_empty());
+
+        game.current_roll = Roll::try_from_dice(vec![6, 6, 6, 1, 2], 6).unwrap();
+        game.attempt_command(&Command::Score(ScoreType::Sixes)).unwrap();
+        assert_eq!(game.notices, [Notice::UpperBonus]);
+        game.notices.clear();
+
+        for score_type in game.player().score_table.open_categories(&game.rules) {
+            game.attempt_command(&Command::Scratch(score_type)).unwrap();
+        }
+        assert_eq!(game.notices, [Notice::GameOver]);
+    }
+
+    #[test]
+    fn loop_commands_are_refused() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        assert_eq!(game.attempt_command(&Command::NotRecognised("x".into())), Err(CommandError::NotRecognised("x".into())));
+        for command in [Command::Quit, Command::Repeat, Command::Load("slot".into()), Command::Theme("classic".into()), Command::Playback("file".into())] {
+            assert_eq!(game.attempt_command(&command), Err(CommandError::NotAvailable), "{:?}", command);
+        }
+        assert_eq!(game.attempt_command(&Command::Help("help text".into())), Ok("help text".to_string()));
+    }
+
+    #[test]
+    fn repeat_runs_the_last_command_again() {
+        let mut last_command = None;
+        assert_eq!(resolve_repeat(Command::Repeat, &mut last_command), None);
+
+        assert_eq!(resolve_repeat(Command::Roll, &mut last_command), Some(Command::Roll));
+        assert_eq!(resolve_repeat(Command::Repeat, &mut last_command), Some(Command::Roll));
+        // a mistyped command or a look at the help doesn't get in the way
+        resolve_repeat(Command::NotRecognised("Invalid command".to_string()), &mut last_command);
+        resolve_repeat(Command::Help("roll".to_string()), &mut last_command);
+        assert_eq!(resolve_repeat(Command::Repeat, &mut last_command), Some(Command::Roll));
+        assert_eq!(last_command, Some(Command::Roll));
+
+        // the headless loop goes through the same helper, a repeat before anything else is just skipped
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.run_headless([Command::Repeat, Command::Roll, Command::Repeat].into_iter());
+        assert_eq!(game.rolls_used, 3);
+    }
+}
@@ -1,320 +1,542 @@
-use core::panic;
-use std::{collections::HashMap, io::{self, stdout, Write, Stdout}};
-
-use crossterm::{ExecutableCommand, terminal, QueueableCommand, cursor, style::{self, Stylize, Color, Attribute}};
-use rand::Rng;
-
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
-enum ScoreType {
-    Aces,
-    Twos,
-    Threes,
-    Fours,
-    Fives,
-    Sixes,
-    FourOfKind,
-    FullHouse,
-    LittleStraight,
-    BigStraight,
-    Yacht,
-    Chance,
+mod game;
+mod json;
+mod persist;
+mod roll;
+mod score;
+mod ui;
+
+use std::{fs, io::{self, stdout, IsTerminal, Write}, sync::mpsc, thread, time};
+
+use crossterm::{ExecutableCommand, terminal, QueueableCommand, cursor, style::{self, Color}, event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers}};
+
+use game::{COMMANDS, Command, Game, GameOptions, GameStates, HintLevel, SessionStats, find_command, resolve_repeat};
+use persist::{AUTOSAVE_PATH, LEADERBOARD_PATH, SAVE_DIR, is_valid_slot_name, record_scores, slot_path, write_atomically};
+use roll::DiceNum;
+use score::{Rules, Ruleset, ScoreTable, ScoreType, scorecard_report};
+use ui::{DiceColors, DiceStyle, DrawValues, GAME_HEIGHT, GAME_WIDTH, RenderState, THEME_NAMES, TerminalGuard, Theme, draw_once, draw_timer, draw_too_small, draw_update, parse_color, ring_bell, leaderboard_panel, terminal_fits, utf8_locale};
+
+struct Config {
+    // everything the game itself is set up with
+    options: GameOptions,
+    autosave: bool,
+    clear_autosave: bool,
+    resume: bool,
+    scorecard: bool,
+    free_color: Color,
+    held_color: Color,
+    set_title: bool,
+    dice_style: DiceStyle,
+    pip: char,
+    sorted_view: bool,
+    // single keypresses for the common commands, with ':' to type anything else
+    keys: bool,
+    theme: Theme,
+    // a yacht, the upper bonus and game over ring the terminal bell
+    bell: bool,
+    // commands come from stdin one per line and only the final state is printed, for scripts
+    headless: bool,
 }
 
-impl ScoreType {
-    fn from_u8(num: u8) -> ScoreType{
-        match num {
-            0 => ScoreType::Aces,
-            1 => ScoreType::Twos,
-            2 => ScoreType::Threes,
-            3 => ScoreType::Fours,
-            4 => ScoreType::Fives,
-            5 => ScoreType::Sixes,
-            6 => ScoreType::FourOfKind,
-            7 => ScoreType::FullHouse,
-            8 => ScoreType::LittleStraight,
-            9 => ScoreType::BigStraight,
-            10 => ScoreType::Yacht,
-            11 => ScoreType::Chance,
-            _ => panic!("Integer exceeds bounds of enum!")
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            options: GameOptions::default(),
+            autosave: false,
+            clear_autosave: false,
+            resume: false,
+            scorecard: false,
+            free_color: Color::White,
+            held_color: Color::Green,
+            set_title: true,
+            dice_style: DiceStyle::Pips,
+            pip: 'O',
+            sorted_view: false,
+            keys: false,
+            theme: Theme::from_name("classic").unwrap(),
+            bell: true,
+            headless: false,
         }
     }
 }
 
-struct ScoreTable {
-    table: HashMap<ScoreType, u8>,
-}
-
-impl ScoreTable {
-    fn new() -> Self {
-        ScoreTable { table: HashMap::new() }
-    }
-
-    fn reset_scores(&mut self) {
-        self.table.clear();
-    }
-
-    fn check_table(&self, score_type: &ScoreType) -> bool {
-        self.table.contains_key(score_type)
-    }
-
-    fn table_total(&self) -> u16 {
-        let mut sum = 0;
-        for (_, score) in &self.table {
-            sum += score;
+impl Config {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut config = Config::default();
+        // the category and the Chance cap are checked once the ruleset and dice are known, whichever order they came in
+        let mut drill = None;
+        let mut chance_cap = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--rolls" => {
+                    let Some(value) = args.next() else { return Err("--rolls needs a number of rolls".to_string()) };
+                    match value.parse::<u8>() {
+                        Ok(rolls) if rolls > 0 => config.options.rolls_per_turn = rolls,
+                        _ => return Err(format!("Invalid roll count '{}', should be at least 1", value)),
+                    }
+                },
+                "--lucky" => config.options.lucky = true,
+                "--debug" => config.options.debug = true,
+                "--autosave" => config.autosave = true,
+                "--clear-autosave" => config.clear_autosave = true,
+                "--scorecard" => config.scorecard = true,
+                "--no-title" => config.set_title = false,
+                "--numeric-dice" => config.dice_style = DiceStyle::Numbers,
+                "--unicode-dice" => config.dice_style = DiceStyle::Unicode,
+                "--sorted-view" => config.sorted_view = true,
+                // a pip takes one cell, so it has to be one character that shows up
+                "--pip" => {
+                    let Some(value) = args.next() else { return Err("--pip needs a symbol, like * or #".to_string()) };
+                    let mut chars = value.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(pip), None) if !pip.is_whitespace() && !pip.is_control() => config.pip = pip,
+                        _ => return Err(format!("Invalid pip '{}', should be a single symbol", value)),
+                    }
+                },
+                "--strict-order" => config.options.rules.strict_order = true,
+                "--roll-first" => config.options.rules.roll_first = true,
+                "--ruleset" => {
+                    let Some(value) = args.next() else { return Err("--ruleset needs a name, yacht or generala".to_string()) };
+                    match Ruleset::from_name(&value) {
+                        Some(ruleset) => config.options.rules.ruleset = ruleset,
+                        None => return Err(format!("Unknown ruleset '{}', try yacht or generala", value)),
+                    }
+                },
+                "--coach" => config.options.coach = true,
+                "--keys" => config.keys = true,
+                "--headless" => config.headless = true,
+                "--ai" => config.options.ai = true,
+                "--confirm-zero" => config.options.confirm_zero = true,
+                "--confirm-better" => config.options.confirm_better = true,
+                "--no-bell" => config.bell = false,
+                "--chance-cap" => {
+                    let Some(value) = args.next() else { return Err("--chance-cap needs a number of points".to_string()) };
+                    chance_cap = Some(value);
+                },
+                // the board has room for six dice, and the upper section needs every face up to 6
+                "--dice" => {
+                    let Some(value) = args.next() else { return Err("--dice needs a number of dice".to_string()) };
+                    match value.parse::<u8>() {
+                        Ok(dice) if (5..=6).contains(&dice) => config.options.rules.dice = dice,
+                        _ => return Err(format!("Invalid number of dice '{}', should be 5 or 6", value)),
+                    }
+                },
+                "--sides" => {
+                    let Some(value) = args.next() else { return Err("--sides needs a number of sides".to_string()) };
+                    match value.parse::<u8>() {
+                        Ok(sides) if (6..=9).contains(&sides) => config.options.rules.sides = sides,
+                        _ => return Err(format!("Invalid number of sides '{}', should be between 6 and 9", value)),
+                    }
+                },
+                "--players" => {
+                    let Some(value) = args.next() else { return Err("--players needs a comma separated list of names".to_string()) };
+                    let names: Vec<String> = value.split(',').map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect();
+                    if names.is_empty() {
+                        return Err("--players needs at least one name".to_string());
+                    }
+                    config.options.players = names;
+                },
+                "--drill" => {
+                    let Some(value) = args.next() else { return Err("--drill needs a category".to_string()) };
+                    drill = Some(value);
+                },
+                "--free-color" | "--held-color" => {
+                    let Some(value) = args.next() else { return Err(format!("{} needs a colour name", arg)) };
+                    let Some(color) = parse_color(&value) else { return Err(format!("Unknown colour '{}'", value)) };
+                    if arg == "--free-color" { config.free_color = color } else { config.held_color = color }
+                },
+                "--continue" => {
+                    config.resume = true;
+                    config.autosave = true;
+                },
+                "--theme" => {
+                    let Some(value) = args.next() else { return Err("--theme needs a theme name".to_string()) };
+                    config.theme = Theme::from_name(&value)?;
+                },
+                "--turn-limit" => {
+                    let Some(value) = args.next() else { return Err("--turn-limit needs a number of seconds".to_string()) };
+                    match value.parse::<u64>() {
+                        Ok(secs) if secs > 0 => config.options.turn_limit = Some(secs),
+                        _ => return Err(format!("Invalid turn limit '{}', should be a whole number of seconds", value)),
+                    }
+                },
+                "--match" => {
+                    let Some(value) = args.next() else { return Err("--match needs a target score".to_string()) };
+                    match value.parse::<u32>() {
+                        Ok(target) if target > 0 => config.options.match_target = Some(target),
+                        _ => return Err(format!("Invalid match target '{}', should be a whole number of points", value)),
+                    }
+                },
+                "--seed" => {
+                    let Some(value) = args.next() else { return Err("--seed needs a number".to_string()) };
+                    match value.parse::<u64>() {
+                        Ok(seed) => config.options.seed = Some(seed),
+                        Err(_) => return Err(format!("Invalid seed '{}', should be a whole number", value)),
+                    }
+                },
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
         }
 
-        sum as u16
-    }
-
-    fn score_on_table(&mut self, score_type: &ScoreType, roll: &Roll) -> bool {
-        if self.check_table(score_type) {
-            return false;
+        // all ones is the least Chance can ever score, so a lower cap would just make it worthless
+        if let Some(value) = chance_cap {
+            let most = ScoreType::Chance.max_score(&config.options.rules);
+            match value.parse::<u8>() {
+                Ok(cap) if (config.options.rules.dice..=most).contains(&cap) => config.options.rules.chance_cap = Some(cap),
+                _ => return Err(format!("Invalid Chance cap '{}', should be between {} and {}", value, config.options.rules.dice, most)),
+            }
         }
 
-        let score = evaluate_score(roll, score_type);
-
-        self.table.insert(*score_type, score);
-
-        true
-
-    }
-
-    fn get_table_value(&self, score_type: &ScoreType) -> String {
-        match self.table.get(score_type) {
-            Some(score) => {
-                return format!(" {}", score)
-            },
-            None => format!(" X "),
+        // pips only go up to six
+        if config.options.rules.sides > 6 {
+            config.dice_style = DiceStyle::Numbers;
         }
-    }
 
-    fn _print_table(&self) {
-        println!("{} - {}", "Aces", self.get_table_value(&ScoreType::Aces));
-        println!("{} - {}", "Twos", self.get_table_value(&ScoreType::Twos));
-        println!("{} - {}", "Threes", self.get_table_value(&ScoreType::Threes));
-        println!("{} - {}", "Fours", self.get_table_value(&ScoreType::Fours));
-        println!("{} - {}", "Fives", self.get_table_value(&ScoreType::Fives));
-        println!("{} - {}", "Sixes", self.get_table_value(&ScoreType::Sixes));
+        if let Some(value) = drill {
+            match ScoreType::from_word(&value, &config.options.rules) {
+                Some(score_type) => config.options.drill = Some(score_type),
+                None => return Err(format!("Unknown category '{}'", value)),
+            }
+        }
 
-        println!("{} - {}", "Four Of A Kind", self.get_table_value(&ScoreType::FourOfKind));
-        println!("{} - {}", "Full House", self.get_table_value(&ScoreType::FullHouse));
-        println!("{} - {}", "Little Straight", self.get_table_value(&ScoreType::LittleStraight));
-        println!("{} - {}", "Big Straight", self.get_table_value(&ScoreType::BigStraight));
-        println!("{} - {}", "Yacht", self.get_table_value(&ScoreType::Yacht));
-        println!("{} - {}", "Chance", self.get_table_value(&ScoreType::Chance));
+        Ok(config)
     }
 }
 
-struct Roll {
-    dice: [u8; 5],
-    holds: [bool; 5],
+// every player's card, compared before and after a command to tell whether there's anything new to autosave
+fn score_cards(game: &Game) -> Vec<ScoreTable> {
+    game.players.iter().map(|player| player.score_table.clone()).collect()
 }
 
-impl Roll {
-    fn new() -> Self {
-        Roll {
-            dice: Roll::gen_roll(),
-            holds: [false; 5],
-        }
-    }
-
-    fn _new_fake(roll_tuple: (u8, u8, u8, u8, u8)) -> Self {
-        Roll {
-            dice: [
-                roll_tuple.0,
-                roll_tuple.1,
-                roll_tuple.2,
-                roll_tuple.3,
-                roll_tuple.4,
-            ],
-            holds: [false; 5],
-        }
+// the title is only sent when it changes, shown is what the terminal was last given
+fn update_title(stdout: &mut impl Write, game: &Game, shown: &mut String) {
+    let title = game.window_title();
+    if title != *shown {
+        stdout.execute(terminal::SetTitle(&title)).unwrap();
+        *shown = title;
     }
+}
 
-    fn sort(&mut self) {
-        self.dice.sort();
+// the autosaved game for --continue, or a new one without it. True as well when the autosave is there but couldn't
+// be loaded, as it shouldn't then be written over
+fn continue_game(path: &str, options: &GameOptions) -> (Game, bool) {
+    match Game::load(path, options) {
+        Ok(mut game) => {
+            game.msg = "Continuing your autosaved game".to_string();
+            (game, false)
+        },
+        Err(msg) => {
+            let mut game = Game::new(options);
+            match fs::metadata(path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    game.msg = "No autosave found, starting a new game".to_string();
+                    (game, false)
+                },
+                _ => {
+                    game.msg = format!("{}, starting a new game with autosave off so it isn't overwritten", msg);
+                    game.msg_error = true;
+                    (game, true)
+                },
+            }
+        },
     }
+}
 
-    fn reset_holds(&mut self) {
-        self.holds = [false; 5];
-    }
+// writes autosaves on a background thread so the disk never holds up drawing the next frame
+struct Autosaver {
+    saves: Option<mpsc::Sender<String>>,
+    errors: mpsc::Receiver<String>,
+    writer: Option<thread::JoinHandle<()>>,
+}
 
-    fn gen_roll() -> [u8; 5] {
-        let mut rng = rand::thread_rng();
+impl Autosaver {
+    fn new(path: String) -> Self {
+        let (saves, pending) = mpsc::channel::<String>();
+        let (report, errors) = mpsc::channel();
 
-        let a = rng.gen_range(1..=6);
-        let b = rng.gen_range(1..=6);
-        let c = rng.gen_range(1..=6);
-        let d = rng.gen_range(1..=6);
-        let e = rng.gen_range(1..=6);
+        let writer = thread::spawn(move || {
+            for contents in pending {
+                if let Err(msg) = write_atomically(&path, &contents) {
+                    let _ = report.send(msg);
+                }
+            }
+        });
 
-        [a, b, c, d, e]
+        Autosaver { saves: Some(saves), errors, writer: Some(writer) }
     }
 
-    fn roll_with_holds(&mut self) {
-        let mut rng = rand::thread_rng();
-
-        for i in 0..5 {
-            if !self.holds[i] {
-                self.dice[i] = rng.gen_range(1..=6);
-            }
+    fn save(&self, game: &Game) {
+        if let Some(saves) = &self.saves {
+            let _ = saves.send(game.to_json().to_string());
         }
     }
 
-    fn hold(&mut self, num: &DiceNum) {
-        self.holds[*num as usize] = true;
+    fn last_error(&self) -> Option<String> {
+        self.errors.try_iter().last()
     }
-}
 
-#[derive(Debug, PartialEq, Copy, Clone)]
-enum DiceNum {
-    First = 0,
-    Second = 1,
-    Third = 2,
-    Fourth = 3,
-    Fifth = 4,
-}
-
-#[derive(PartialEq, Debug)]
-enum GameStates {
-    FirstRoll,
-    SecondRoll,
-    ThirdRoll,
-    GameOver,
-}
-
-struct Game {
-    game_state: GameStates,
-    current_roll: Roll,
-    score_table: ScoreTable,
-    msg: String,
+    // waits for any queued saves to hit the disk
+    fn finish(&mut self) {
+        self.saves = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
 }
 
-impl Game {
-    fn new() -> Self {
-        Game {
-            game_state: GameStates::FirstRoll,
-            current_roll: Roll::new(),
-            score_table: ScoreTable::new(),
-            msg: "".to_string(),
+fn main() {
+    
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            std::process::exit(1);
         }
-    }
+    };
 
-    fn advance_gamestate(&mut self) {
-        match self.game_state {
-            GameStates::FirstRoll => self.game_state = GameStates::SecondRoll,
-            GameStates::SecondRoll => self.game_state = GameStates::ThirdRoll,
-            GameStates::ThirdRoll => panic!("Cant advance from Third roll without score"),
-            GameStates::GameOver => panic!("This should probably start a new game"),
+    if config.scorecard {
+        let categories = config.options.rules.categories();
+        let last = categories.last().map_or("", ScoreType::name);
+        println!("Enter the {} category scores in table order (Aces to {}), then end input with Ctrl-D:", categories.len(), last);
+        let mut input = String::new();
+        io::Read::read_to_string(&mut io::stdin(), &mut input).expect("failed to read scorecard");
+        for line in scorecard_report(&input, &config.options.rules) {
+            println!("{}", line);
         }
+        return;
     }
 
-    fn attempt_command(&mut self, command: &Command) -> Result<String, String>{
-        match command {
-            Command::Roll => {
-                if self.game_state == GameStates::ThirdRoll {
-                    return Ok("No more rolls available this round, try 'score'".to_string())
-                }
-
-                self.current_roll.roll_with_holds();
-
-                self.advance_gamestate();
+    // an autosave that's there but couldn't be loaded is left alone rather than written over by the new game
+    let mut autosave_unreadable = false;
+
+    let mut game = if let Some(target) = config.options.drill {
+        let mut game = Game::new(&config.options);
+        game.msg = format!("Drilling {}: roll for it, then 'score' any category to end the attempt", target.name());
+        game
+    } else if config.resume {
+        let (game, unreadable) = continue_game(AUTOSAVE_PATH, &config.options);
+        autosave_unreadable = unreadable;
+        game
+    } else {
+        Game::new(&config.options)
+    };
 
-                Ok("Onto next roll".to_string())
-            },
-            Command::Sort => {
-                self.current_roll.sort();
-                self.current_roll.reset_holds();
-                Ok("Dice Sorted!".to_string())
-            },
-            Command::Score(score_type) => {
-                if self.score_table.score_on_table(&score_type, &self.current_roll) {
-                    if self.score_table.table.len() == 12 {
-                        self.game_state = GameStates::GameOver;
-                        return Ok("Game Over! Type 'new' to start a new game!".to_string());
-                    }
-                    self.game_state = GameStates::FirstRoll;
-                    self.current_roll = Roll::new();
-                    Ok("Score submitted!".to_string())
-                } else {
-                    Ok("That score type was already used!".to_string())
-                }
-            },
-            Command::Hold(hold_num) => {
-                self.current_roll.hold(hold_num);
-                Ok(format!("Held dice number {}", *hold_num as u8 + 1))
-            },
-            Command::New => {
-                self.score_table.reset_scores();
-                self.current_roll.roll_with_holds();
-                self.game_state = GameStates::FirstRoll;
-                Ok("New Game Started".to_string())
-            },
-            Command::NotRecognised(_) => todo!(),
-            _ => panic!("Don't know how this happened, may quit wasn't handled?")
+    if config.headless {
+        let commands = io::stdin()
+            .lines()
+            .map_while(Result::ok)
+            .map(|line| command_from_line(if line.len() <= MAX_INPUT_LEN { InputLine::Line(line) } else { InputLine::TooLong }, &config.options.rules));
+        for line in game.run_headless(commands).report() {
+            println!("{}", line);
         }
+        return;
     }
-}
 
-#[derive(Debug, PartialEq)]
-enum Command {
-    Roll,
-    Sort,
-    Score(ScoreType),
-    Hold(DiceNum),
-    New,
-    Quit,
-    Help(String),
-    NotRecognised(String),
-}
+    let mut stdout = stdout();
 
+    let _terminal = TerminalGuard::new();
 
-struct DrawValues {
-    score_table_corner: (u16, u16),
-    dice_corner: (u16, u16),
-    game_status_pos: (u16, u16),
-    prompt_pos: (u16, u16),
-    title_pos: (u16, u16),
-}
+    // a turn limit needs input that can be waited on with a timeout, keys mode polls for its keypresses instead
+    let lines = if config.options.turn_limit.is_some() && !config.keys { Some(LineReader::new()) } else { None };
 
-const GAME_WIDTH: u16 = 95;
-const GAME_HEIGHT: u16 = 35;
+    let mut autosaver = if config.autosave && !autosave_unreadable { Some(Autosaver::new(AUTOSAVE_PATH.to_string())) } else { None };
 
-fn main() {
-    
-    let mut game = Game::new();
-    let mut stdout = stdout();
+    let mut draw_values = board_layout(&config);
 
-    let draw_values = DrawValues {
-        score_table_corner: (3, 3),
-        dice_corner: (35, 12),
-        game_status_pos: (60, 3),
-        prompt_pos: (3, 30),
-        title_pos: (40, 0)
-    };
+    // the board is drawn on the first pass of the loop, or again once a too small terminal is enlarged
+    let mut board_drawn = false;
+    let mut render_state = RenderState::default();
 
-    draw_once(&mut stdout, &draw_values);
+    let mut window_title = String::new();
+    let mut last_command: Option<Command> = None;
+    let mut history = InputHistory { lines: Vec::new() };
     
     loop {
 
-        draw_update(&game, &mut stdout, &draw_values);
+        if config.set_title {
+            update_title(&mut stdout, &game, &mut window_title);
+        }
+
+        // commands can still be typed while the board is hidden, 'quit' included
+        // output going somewhere other than a terminal, like a file, has no size to fit
+        let size = if stdout.is_terminal() { terminal::size().unwrap_or((GAME_WIDTH, GAME_HEIGHT)) } else { (GAME_WIDTH, GAME_HEIGHT) };
+        if !terminal_fits(size) {
+            draw_too_small(&mut stdout, size);
+            board_drawn = false;
+        } else {
+            if !board_drawn {
+                draw_once(&mut stdout, &draw_values, &mut render_state);
+                board_drawn = true;
+            }
+            draw_update(&game, &mut stdout, &draw_values, &mut render_state);
+        }
 
-        let mut command = retrieve_command();
+        // rung after the draw so it sounds along with the board that shows why. Output that isn't a terminal
+        // gets no bell, it would only be a stray control character in a file
+        if !game.notices.is_empty() {
+            if config.bell && stdout.is_terminal() {
+                ring_bell(&mut stdout);
+            }
+            game.notices.clear();
+        }
+
+        if game.awaiting_name {
+            game.awaiting_name = false;
+            let name = match &lines {
+                Some(lines) => lines.next_line(),
+                None => read_capped_line(&mut io::stdin().lock()),
+            };
+            let name = match &name {
+                InputLine::Line(name) => name.trim(),
+                InputLine::TooLong | InputLine::Closed => "",
+            };
+            if name.is_empty() {
+                game.msg = "Score not recorded. Type 'new' to start a new game!".to_string();
+                continue;
+            }
+            game.msg = match record_scores(LEADERBOARD_PATH, &[(name.to_string(), game.total())]) {
+                Ok(()) => format!("Recorded {} for {}. Type 'new' to start a new game!", game.total(), name),
+                Err(e) => e,
+            };
+            game.panel = leaderboard_panel();
+            continue;
+        }
+
+        let input = if game.game_state == GameStates::Rolling && game.player().is_ai {
+            thread::sleep(AI_DELAY);
+            Some(game.ai_command())
+        } else if config.keys {
+            retrieve_key_command(game.turn_deadline(), &config.options.rules, &mut history, || draw_timer(&game, &mut stdout, &draw_values))
+        } else if let Some(lines) = &lines {
+            match game.turn_deadline() {
+                Some(deadline) => retrieve_timed_command(lines, deadline, &config.options.rules, || draw_timer(&game, &mut stdout, &draw_values)),
+                None => Some(command_from_line(lines.next_line(), &config.options.rules)),
+            }
+        } else {
+            Some(retrieve_command(&config.options.rules))
+        };
+
+        // a turn that ran out of time is scratched, whatever was typed after it did
+        let timeout_scratch = if game.out_of_time() && input != Some(Command::Quit) { game.timeout_scratch() } else { None };
+        let command = match (timeout_scratch, input) {
+            (Some(score_type), _) => Command::Scratch(score_type),
+            (None, Some(command)) => command,
+            // only out of time mid turn, which always has a category open
+            (None, None) => continue,
+        };
+
+        game.panel.clear();
+        game.highlight = None;
+        game.preview = false;
+        game.msg_error = false;
+
+        // a scratch for running out of time wasn't asked for, so it isn't one to repeat
+        let mut command = if timeout_scratch.is_some() {
+            command
+        } else {
+            let Some(command) = resolve_repeat(command, &mut last_command) else {
+                game.msg = "Nothing to repeat yet".to_string();
+                game.msg_error = true;
+                continue;
+            };
+            command
+        };
 
         if command == Command::Quit {
             break;
         }
 
-        if let GameStates::GameOver = game.game_state {
+        // rolling on from a finished game starts the next one, anything that would change the card is refused by the game
+        if game.game_state == GameStates::GameOver && command == Command::Roll {
             command = Command::New;
         }
 
-        if let Command::New = command {
-            draw_once(&mut stdout, &draw_values);
+        if matches!(command, Command::New | Command::Daily) {
+            draw_once(&mut stdout, &draw_values, &mut render_state);
+        }
+
+        // loading swaps in a whole new game, so it's handled here rather than by the game itself
+        if let Command::Load(name) = &command {
+            match Game::load(&slot_path(SAVE_DIR, name), &config.options) {
+                Ok(mut loaded) => {
+                    loaded.session = std::mem::replace(&mut game.session, SessionStats { games_played: 0, total_points: 0 });
+                    loaded.hint_override = game.hint_override;
+                    loaded.coach = game.coach;
+                    loaded.confirm_zero = game.confirm_zero;
+                    loaded.confirm_better = game.confirm_better;
+                    loaded.msg = format!("Loaded '{}'", name);
+                    loaded.start_recording();
+                    game = loaded;
+                    draw_once(&mut stdout, &draw_values, &mut render_state);
+                },
+                Err(e) => {
+                    game.msg = e;
+                    game.msg_error = true;
+                },
+            }
+            continue;
+        }
+
+        // playing back swaps in the recorded game too, then makes its moves one at a time so they can be followed.
+        // The computer takes its own turns as it did when the game was recorded
+        if let Command::Playback(path) = &command {
+            match Game::load_recording(path, &config.options) {
+                Ok((mut played, moves)) => {
+                    played.session = std::mem::replace(&mut game.session, SessionStats { games_played: 0, total_points: 0 });
+                    played.coach = game.coach;
+                    played.start_recording();
+                    game = played;
+                    draw_once(&mut stdout, &draw_values, &mut render_state);
+
+                    let mut moves = moves.into_iter();
+                    loop {
+                        let step = if game.game_state == GameStates::Rolling && game.player().is_ai {
+                            game.ai_command()
+                        } else {
+                            match moves.next() {
+                                Some(line) => command_from_line(InputLine::Line(line), &config.options.rules),
+                                None => break,
+                            }
+                        };
+                        thread::sleep(PLAYBACK_DELAY);
+                        let result = game.attempt_command(&step);
+                        game.msg_error = result.is_err();
+                        game.msg = result.unwrap_or_else(|e| e.to_string());
+                        draw_update(&game, &mut stdout, &draw_values, &mut render_state);
+                    }
+
+                    // the score was recorded when the game was played
+                    game.awaiting_name = false;
+                    game.record_pending = false;
+                    game.msg = format!("Played back {}", path);
+                },
+                Err(e) => {
+                    game.msg = e;
+                    game.msg_error = true;
+                },
+            }
+            continue;
+        }
+
+        // colours belong to the drawing rather than the game, so switching them is handled here too
+        if let Command::Theme(name) = &command {
+            match Theme::from_name(name) {
+                Ok(theme) => {
+                    draw_values.theme = theme;
+                    game.msg = format!("Switched to the {} theme", name);
+                    draw_once(&mut stdout, &draw_values, &mut render_state);
+                },
+                Err(e) => {
+                    game.msg = e;
+                    game.msg_error = true;
+                },
+            }
+            continue;
         }
 
         if let Command::NotRecognised(msg) = command {
             game.msg = msg;
+            game.msg_error = true;
             continue;
         }
 
@@ -323,335 +545,484 @@ fn main() {
             continue;
         }
 
+        // a score can be committed by 'yes' as well as 'score', and undo can take one back, so the autosave
+        // goes by whether any card changed rather than by the command
+        let cards_before = score_cards(&game);
+
         let result = game.attempt_command(&command);
 
-        game.msg = result.unwrap();
+        // errors are for the player to read too, like a save that couldn't be written
+        game.msg_error = result.is_err();
+        game.msg = result.unwrap_or_else(|e| e.to_string());
+        if timeout_scratch.is_some() {
+            game.msg = format!("Time's up! {}", game.msg);
+        }
+
+        if game.record_pending {
+            game.record_pending = false;
+            if let Err(e) = record_scores(LEADERBOARD_PATH, &game.final_scores()) {
+                game.msg = format!("{} {}", game.msg, e);
+                game.msg_error = true;
+            }
+        }
+
+        if let Some(autosaver) = &autosaver {
+            if score_cards(&game) != cards_before {
+                autosaver.save(&game);
+            }
+            if let Some(msg) = autosaver.last_error() {
+                game.msg = msg;
+                game.msg_error = true;
+            }
+        }
+    }
+
+    if config.set_title {
+        stdout.execute(terminal::SetTitle("")).unwrap();
+    }
+
+    if let Some(autosaver) = &mut autosaver {
+        autosaver.finish();
+        if config.clear_autosave {
+            let _ = fs::remove_file(AUTOSAVE_PATH);
+        }
     }
 }
 
-fn draw_once(stdout: &mut Stdout, values: &DrawValues) {
+// no command comes close to this, anything longer is a stray paste
+const MAX_INPUT_LEN: usize = 256;
+
+// what reading a line of input came to: the line itself, one too long to keep, or nothing at all because
+// stdin has ended
+enum InputLine {
+    Line(String),
+    // longer than MAX_INPUT_LEN, so dropped
+    TooLong,
+    // stdin has ended, like a piped file running out or Ctrl-D, so no more commands are coming
+    Closed,
+}
 
-    let mut score_name: Vec<String> = Vec::new();
+// reads one line while keeping at most MAX_INPUT_LEN bytes of it in memory
+fn read_capped_line(reader: &mut impl io::BufRead) -> InputLine {
+    let mut line = Vec::new();
+    let mut too_long = false;
 
-    score_name.push("1  - Aces".to_string());
-    score_name.push("2  - Twos".to_string());
-    score_name.push("3  - Threes".to_string());
-    score_name.push("4  - Fours".to_string());
-    score_name.push("5  - Fives".to_string());
-    score_name.push("6  - Sixes".to_string());
-    score_name.push("7  - Four Of A Kind".to_string());
-    score_name.push("8  - Full House".to_string());
-    score_name.push("9  - Little Straight".to_string());
-    score_name.push("10 - Big Straight".to_string());
-    score_name.push("11 - Yacht".to_string());
-    score_name.push("12 - Chance".to_string());
+    loop {
+        // an error reading stdin leaves nothing more to read, the same as it ending
+        let Ok(buf) = reader.fill_buf() else { return InputLine::Closed };
+        if buf.is_empty() {
+            if line.is_empty() && !too_long {
+                return InputLine::Closed;
+            }
+            break;
+        }
 
-    stdout.execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+        let (chunk_len, found_newline) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (buf.len(), false),
+        };
 
-    //border
-    for y in 0..GAME_HEIGHT {
-        for x in 0..GAME_WIDTH {
-        if (y == 0 || y == GAME_HEIGHT - 1) || (x == 0 || x == GAME_WIDTH - 1) {
-            // in this loop we are more efficient by not flushing the buffer.
-            stdout
-            .queue(cursor::MoveTo(x,y)).unwrap()
-            .queue(style::PrintStyledContent( "▓".white())).unwrap();
+        if !too_long {
+            if line.len() + chunk_len > MAX_INPUT_LEN + 1 {
+                too_long = true;
+                line = Vec::new();
+            } else {
+                line.extend_from_slice(&buf[..chunk_len]);
+            }
         }
+
+        reader.consume(chunk_len);
+
+        if found_newline {
+            break;
         }
     }
 
-    stdout.queue(cursor::MoveTo(values.title_pos.0, values.title_pos.1)).unwrap();
-    stdout.queue(style::PrintStyledContent(" YACHT DICE "
-            .with(Color::Black)
-            .on(Color::White)
-            .attribute(Attribute::Bold)
-        )).unwrap();
+    if too_long {
+        InputLine::TooLong
+    } else {
+        InputLine::Line(String::from_utf8_lossy(&line).into_owned())
+    }
+}
+
+// the commands that have a key of their own in --keys mode
+fn command_for_key(key: &KeyEvent, rules: &Rules) -> Option<Command> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return if key.code == KeyCode::Char('c') { Some(Command::Quit) } else { None };
+    }
+
+    match key.code {
+        KeyCode::Char(c @ '1'..='9') => DiceNum::from_number(c as u8 - b'0', rules.dice).map(|num| Command::HoldMany(vec![num], Vec::new())),
+        KeyCode::Char('r') => Some(Command::Roll),
+        KeyCode::Char('s') => Some(Command::Sort),
+        KeyCode::Char('p') => Some(Command::Preview),
+        KeyCode::Char('u') => Some(Command::Undo),
+        KeyCode::Char('.') => Some(Command::Repeat),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Command::Quit),
+        _ => None,
+    }
+}
 
-    //DRAW SCORE TABLE
-    let top_corner = values.score_table_corner;
+// raw mode is only on while waiting for the key, so everything else reads and prints as normal
+// None when the deadline passes before a key is pressed, tick runs about once a second while waiting for one
+fn retrieve_key_command(deadline: Option<time::Instant>, rules: &Rules, history: &mut InputHistory, mut tick: impl FnMut()) -> Option<Command> {
+    terminal::enable_raw_mode().unwrap();
+
+    let command = loop {
+        if let Some(deadline) = deadline {
+            let wait = deadline.saturating_duration_since(time::Instant::now());
+            if wait.is_zero() {
+                terminal::disable_raw_mode().unwrap();
+                return None;
+            }
+            if !event::poll(wait.min(TIMER_TICK)).unwrap() {
+                tick();
+                continue;
+            }
+        }
 
-    stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 - 1)).unwrap();
-    stdout.queue(style::Print("╔═SCORE TABLE══════════╤═══╗")).unwrap();
+        let Event::Key(key) = event::read().unwrap() else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if let Some(command) = command_for_key(&key, rules) {
+            break Some(command);
+        }
+        if matches!(key.code, KeyCode::Char(':') | KeyCode::Enter) {
+            break None;
+        }
+    };
 
-    for i in 0..12 {
-        stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + (i*2))).unwrap();
-        print!("║ {}", score_name[i as usize]);
+    terminal::disable_raw_mode().unwrap();
 
-        stdout.queue(cursor::MoveTo(top_corner.0 + 24, top_corner.1 + (i*2))).unwrap();
-        stdout.queue(style::Print("   ║")).unwrap();
+    // anything without a key is typed out as a full command line
+    Some(command.unwrap_or_else(|| command_from_line(read_history_line(history), rules)))
+}
 
-        stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + (i*2) + 1)).unwrap();
+// command lines typed in keys mode, oldest first, for the up and down arrows to bring back
+struct InputHistory {
+    lines: Vec<String>,
+}
 
-        if i != 11 {
-            stdout.queue(style::Print("╟━━━━━━━━━━━━━━━━━━━━━━╋━━━╢")).unwrap();
-        } else {
-            stdout.queue(style::Print("╟━━━━━━━━━━━━━━━━━━━━┯━┻━━━╢")).unwrap();
+impl InputHistory {
+    // a line typed twice in a row is only kept once, like most shells
+    fn push(&mut self, line: &str) {
+        if !line.is_empty() && self.lines.last().map(String::as_str) != Some(line) {
+            self.lines.push(line.to_string());
         }
-        
     }
 
-    stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + 24)).unwrap();
+    // where the arrows move to from pos, where lines.len() is the new line being typed. Stops at either end
+    fn step(&self, pos: usize, older: bool) -> usize {
+        if older { pos.saturating_sub(1) } else { (pos + 1).min(self.lines.len()) }
+    }
+}
 
-    stdout.queue(style::Print("║ TOTAL              │     ║")).unwrap();
+// reads a line in raw mode so the arrows can recall earlier lines, never more than MAX_INPUT_LEN of it.
+// Esc clears the line, Ctrl-C quits and so does Ctrl-D on an empty line, as it would end stdin outside raw mode
+fn read_history_line(history: &mut InputHistory) -> InputLine {
+    terminal::enable_raw_mode().unwrap();
+    let mut stdout = stdout();
+    let start = cursor::position().map_or(0, |(col, _)| col);
 
-    stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + 25)).unwrap();
+    let mut line = String::new();
+    // the line being typed is kept while browsing, so coming back down to it doesn't lose it
+    let mut draft = String::new();
+    let mut pos = history.lines.len();
 
-    stdout.queue(style::Print("╚════════════════════╧═════╝")).unwrap();
+    loop {
+        let Event::Key(key) = event::read().unwrap() else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
 
-    //DRAW DICE
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                line = "quit".to_string();
+                break;
+            },
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) && line.is_empty() => {
+                line = "quit".to_string();
+                break;
+            },
+            KeyCode::Enter => break,
+            KeyCode::Char(c) if line.len() < MAX_INPUT_LEN => line.push(c),
+            KeyCode::Backspace => {
+                line.pop();
+            },
+            KeyCode::Esc => line.clear(),
+            KeyCode::Up | KeyCode::Down => {
+                if pos == history.lines.len() {
+                    draft = line.clone();
+                }
+                pos = history.step(pos, key.code == KeyCode::Up);
+                line = history.lines.get(pos).cloned().unwrap_or_else(|| draft.clone());
+            },
+            _ => continue,
+        }
 
-    let dice_corner = values.dice_corner;
-    
-    stdout.queue(cursor::MoveTo(dice_corner.0, dice_corner.1)).unwrap();
-    stdout.queue(style::Print("┌───────┐  ┌───────┐  ┌───────┐  ┌───────┐  ┌───────┐")).unwrap();
-    stdout.queue(cursor::MoveTo(dice_corner.0, dice_corner.1 + 1)).unwrap();
-    stdout.queue(style::Print("│       │  │       │  │       │  │       │  │       │")).unwrap();
-    stdout.queue(cursor::MoveTo(dice_corner.0, dice_corner.1 + 2)).unwrap();
-    stdout.queue(style::Print("│       │  │       │  │       │  │       │  │       │")).unwrap();
-    stdout.queue(cursor::MoveTo(dice_corner.0, dice_corner.1 + 3)).unwrap();
-    stdout.queue(style::Print("│       │  │       │  │       │  │       │  │       │")).unwrap();
-    stdout.queue(cursor::MoveTo(dice_corner.0, dice_corner.1 + 4)).unwrap();
-    stdout.queue(style::Print("└───────┘  └───────┘  └───────┘  └───────┘  └───────┘")).unwrap();
-
-    stdout.flush().unwrap();
+        stdout.queue(cursor::MoveToColumn(start)).unwrap();
+        stdout.queue(terminal::Clear(terminal::ClearType::UntilNewLine)).unwrap();
+        stdout.queue(style::Print(&line)).unwrap();
+        stdout.flush().unwrap();
+    }
 
-}
+    terminal::disable_raw_mode().unwrap();
+    println!();
 
-fn draw_update(game: &Game, stdout: &mut Stdout, values: &DrawValues) {
-
-    let mut score_status: Vec<String> = Vec::new();
-
-    for score_type in 0..12 {
-        let x = game.score_table.get_table_value(&ScoreType::from_u8(score_type));
-        score_status.push(x);
-    }
-
-    //Draw Table Values
-
-    let top_corner = values.score_table_corner;
-
-    for i in 0..12 {
-
-        stdout.queue(cursor::MoveTo(top_corner.0 + 23, top_corner.1 + (i*2))).unwrap();
-        print!("┃{}", score_status[i as usize]);
-    }
-
-    stdout.queue(cursor::MoveTo(top_corner.0 + 23, top_corner.1 + 24)).unwrap();
-    let total = format!("{}  ", game.score_table.table_total());
-    stdout.queue(style::Print(total)).unwrap();
-
-    //DRAW GAME STATE
-    stdout.queue(cursor::MoveTo(values.game_status_pos.0, values.game_status_pos.1)).unwrap();
-    stdout.queue(style::Print("Game Status:               ")).unwrap();
-    stdout.queue(cursor::MoveTo(values.game_status_pos.0 + 12, values.game_status_pos.1)).unwrap();
-    match game.game_state {
-        GameStates::FirstRoll => stdout.queue(style::PrintStyledContent(" First Roll "
-            .with(Color::Yellow)
-            .on(Color::Green)
-            .attribute(Attribute::Bold)
-        )).unwrap(),
-        GameStates::SecondRoll => stdout.queue(style::PrintStyledContent(" Second Roll "
-            .with(Color::Black)
-            .on(Color::Yellow)
-            .attribute(Attribute::Bold)
-        )).unwrap(),
-        GameStates::ThirdRoll => stdout.queue(style::PrintStyledContent(" Final Roll "
-            .with(Color::White)
-            .on(Color::Red)
-            .attribute(Attribute::Bold)
-        )).unwrap(),
-        GameStates::GameOver => stdout.queue(style::PrintStyledContent(" GAME OVER "
-            .with(Color::Yellow)
-            .on(Color::Blue)
-            .attribute(Attribute::Bold)
-        )).unwrap(),
-    };
-    
-    let dice_corner = values.dice_corner;
-    //draw faces
-
-    draw_dice_at(stdout, (dice_corner.0 + 4, dice_corner.1 + 2), game.current_roll.dice[0]);
-    draw_dice_at(stdout, (dice_corner.0 + 15, dice_corner.1 + 2), game.current_roll.dice[1]);
-    draw_dice_at(stdout, (dice_corner.0 + 26, dice_corner.1 + 2), game.current_roll.dice[2]);
-    draw_dice_at(stdout, (dice_corner.0 + 37, dice_corner.1 + 2), game.current_roll.dice[3]);
-    draw_dice_at(stdout, (dice_corner.0 + 48, dice_corner.1 + 2), game.current_roll.dice[4]);
-
-    //draw holds
-    stdout.queue(cursor::MoveTo(dice_corner.0 + 3, dice_corner.1 + 6)).unwrap();
-    if game.current_roll.holds[0] { print!("[X]") } else { print!("[ ]") }
-    stdout.queue(cursor::MoveTo(dice_corner.0 + 14, dice_corner.1 + 6)).unwrap();
-    if game.current_roll.holds[1] { print!("[X]") } else { print!("[ ]") }
-    stdout.queue(cursor::MoveTo(dice_corner.0 + 25, dice_corner.1 + 6)).unwrap();
-    if game.current_roll.holds[2] { print!("[X]") } else { print!("[ ]") }
-    stdout.queue(cursor::MoveTo(dice_corner.0 + 36, dice_corner.1 + 6)).unwrap();
-    if game.current_roll.holds[3] { print!("[X]") } else { print!("[ ]") }
-    stdout.queue(cursor::MoveTo(dice_corner.0 + 47, dice_corner.1 + 6)).unwrap();
-    if game.current_roll.holds[4] { print!("[X]") } else { print!("[ ]") }
-
-
-    //cursor to input
-
-    let prompt = values.prompt_pos;
-
-    stdout.queue(cursor::MoveTo(prompt.0, prompt.1)).unwrap();
-    stdout.queue(style::Print("                                                                                    ")).unwrap();
-    let msg_line = format!("--] {}", game.msg);
-    stdout.queue(cursor::MoveTo(prompt.0, prompt.1)).unwrap();
-    stdout.queue(style::Print(msg_line)).unwrap();
-
-    stdout.queue(cursor::MoveTo(prompt.0, prompt.1 + 2)).unwrap();
-    stdout.queue(style::Print("-->                                              ")).unwrap();
-    stdout.queue(cursor::MoveTo(prompt.0 + 4, prompt.1 + 2)).unwrap();
-
-    stdout.flush().unwrap();
+    history.push(&line);
+    InputLine::Line(line)
 }
 
-fn draw_dice_at(stdout: &mut io::Stdout, dice_center: (u16, u16), num: u8) {
-    stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 - 1)).unwrap();
-    stdout.queue(style::Print("     ")).unwrap();
-    stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1)).unwrap();
-    stdout.queue(style::Print("     ")).unwrap();
-    stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
-    stdout.queue(style::Print("     ")).unwrap();
+// how often the turn timer is redrawn while waiting for input
+const TIMER_TICK: time::Duration = time::Duration::from_secs(1);
 
-    stdout.queue(cursor::MoveTo(dice_center.0, dice_center.1)).unwrap();
+// lines typed at the prompt, read on a thread of their own so the turn timer keeps ticking while nothing is typed.
+// The thread only reads the next line once the last one has been taken
+struct LineReader {
+    lines: mpsc::Receiver<InputLine>,
+}
 
-    let dot_symbol = "O";
-    
-    match num {
-        1 => {
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-        },
-        2 => {
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-        },
-        3 => {
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-        },
-        4 => {
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
+impl LineReader {
+    fn new() -> Self {
+        let (sender, lines) = mpsc::sync_channel(0);
+        // once stdin has ended the thread does too, which the receiving end also takes as Closed
+        thread::spawn(move || loop {
+            let line = read_capped_line(&mut io::stdin().lock());
+            let closed = matches!(line, InputLine::Closed);
+            if sender.send(line).is_err() || closed {
+                break;
+            }
+        });
+        LineReader { lines }
+    }
 
-        },
-        5 => {
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
+    fn next_line(&self) -> InputLine {
+        self.lines.recv().unwrap_or(InputLine::Closed)
+    }
+}
 
-        },
-        6 => {
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 - 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 + 1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1)).unwrap();
-            stdout.queue(style::Print(dot_symbol)).unwrap();
-        },
-        _ => panic!("Tried to draw non-dice face")
+// None when the deadline passes before a line is entered
+fn retrieve_timed_command(lines: &LineReader, deadline: time::Instant, rules: &Rules, mut tick: impl FnMut()) -> Option<Command> {
+    loop {
+        let wait = deadline.saturating_duration_since(time::Instant::now());
+        if wait.is_zero() {
+            return None;
+        }
+        match lines.lines.recv_timeout(wait.min(TIMER_TICK)) {
+            Ok(line) => return Some(command_from_line(line, rules)),
+            Err(mpsc::RecvTimeoutError::Timeout) => tick(),
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Some(Command::Quit),
+        }
     }
 }
 
-fn retrieve_command() -> Command {
-    let mut raw_input = String::new();
+fn retrieve_command(rules: &Rules) -> Command {
+    command_from_line(read_capped_line(&mut io::stdin().lock()), rules)
+}
 
-    io::stdin().read_line(&mut raw_input).expect("failed to readline");
+fn command_from_line(line: InputLine, rules: &Rules) -> Command {
+    let raw_input = match line {
+        InputLine::Line(raw_input) => raw_input,
+        InputLine::TooLong => return Command::NotRecognised(format!("Input too long, commands are at most {} characters", MAX_INPUT_LEN)),
+        InputLine::Closed => return Command::Quit,
+    };
 
-    let input: Vec<&str> = raw_input.split_whitespace().collect();
+    let words = match split_words(&raw_input) {
+        Ok(words) => words,
+        Err(e) => return Command::NotRecognised(e),
+    };
+    let input: Vec<&str> = words.iter().map(String::as_str).collect();
 
-    parse_command_from_input(input)
+    parse_command_from_input(input, rules)
 }
 
-fn parse_command_from_input(input: Vec<&str>) -> Command {
-    let Some(first) = input.first() else { return Command::NotRecognised("No input found".to_string())};
-    match *first {
-        "r" | "roll" => Command::Roll,
-        "s" | "sort" => Command::Sort,
-        "h" | "hold" => {
-            if let Some(num) = input.get(1) {
-                if let Ok(i) = num.parse::<u8>() {
-                    match i {
-                        1 => Command::Hold(DiceNum::First),
-                        2 => Command::Hold(DiceNum::Second),
-                        3 => Command::Hold(DiceNum::Third),
-                        4 => Command::Hold(DiceNum::Fourth),
-                        5 => Command::Hold(DiceNum::Fifth),
-                        _ => Command::NotRecognised("Invalid Dice Number, should be (1-5)".to_string()),
-                    }
-                } else {
-                    return Command::NotRecognised("Unable to parse dice number (did you enter a number?)".to_string());
+// splits on whitespace like split_whitespace, except "quoted text" stays together as one word
+fn split_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            },
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
                 }
-                
-            } else {
-                return Command::NotRecognised("Couldn't find command args".to_string());
+            },
+            c => {
+                word.push(c);
+                in_word = true;
+            },
+        }
+    }
+
+    if quoted {
+        return Err("Missing closing quote".to_string());
+    }
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+// where everything goes on the board and how it's drawn, for the config given on the command line
+fn board_layout(config: &Config) -> DrawValues {
+    DrawValues {
+        dice_colors: DiceColors { free: config.free_color, held: config.held_color, wanted: Color::Yellow },
+        // the die glyphs need a UTF-8 terminal, anything else gets the pips drawn out instead
+        dice_style: if config.dice_style == DiceStyle::Unicode && !utf8_locale() { DiceStyle::Pips } else { config.dice_style },
+        pip: config.pip,
+        sorted_view: config.sorted_view,
+        theme: config.theme,
+        categories: config.options.rules.categories(),
+        ..DrawValues::default()
+    }
+}
+
+// each die named is toggled, whatever isn't a die is passed along to be reported
+fn toggle_command(args: &[&str], rules: &Rules) -> Command {
+    let mut nums = Vec::new();
+    let mut rejected = Vec::new();
+    for arg in args {
+        match arg.parse::<u8>().ok().and_then(|num| DiceNum::from_number(num, rules.dice)) {
+            Some(num) => nums.push(num),
+            None => rejected.push(arg.to_string()),
+        }
+    }
+    Command::HoldMany(nums, rejected)
+}
+
+fn parse_command_from_input(input: Vec<&str>, rules: &Rules) -> Command {
+    let Some(first) = input.first() else { return Command::NotRecognised("No input found".to_string())};
+    // command words and keywords are matched in any case, names and paths are left as typed
+    let Some(info) = find_command(&first.to_lowercase()) else {
+        return Command::NotRecognised("Invalid command, try 'help' for list of commands".to_string());
+    };
+
+    match info.name {
+        "roll" => Command::Roll,
+        "reroll" => {
+            let nums: Option<Vec<DiceNum>> = input[1..].iter().map(|arg| arg.parse::<u8>().ok().and_then(|num| DiceNum::from_number(num, rules.dice))).collect();
+            match nums {
+                Some(nums) if !nums.is_empty() => Command::ReRoll(nums),
+                Some(_) => Command::NotRecognised("No dice to reroll found".to_string()),
+                None => Command::NotRecognised(format!("Invalid Dice Number, should be (1-{})", rules.dice)),
             }
         },
-        "sc" | "score" => {
-            if let Some(arg) = input.get(1) {
-                match *arg {
-                    "1" | "aces" => Command::Score(ScoreType::Aces),
-                    "2" | "twos" => Command::Score(ScoreType::Twos),
-                    "3" | "threes" => Command::Score(ScoreType::Threes),
-                    "4" | "fours" => Command::Score(ScoreType::Fours),
-                    "5" | "fives" => Command::Score(ScoreType::Fives),
-                    "6" | "sixes" => Command::Score(ScoreType::Sixes),
-
-                    "7" | "fourofakind" => Command::Score(ScoreType::FourOfKind),
-                    "8" | "fullhouse" => Command::Score(ScoreType::FullHouse),
-                    "9" | "littlestraight" => Command::Score(ScoreType::LittleStraight),
-                    "10" | "bigstraight" => Command::Score(ScoreType::BigStraight),
-                    "11" | "yacht" => Command::Score(ScoreType::Yacht),
-                    "12" | "chance" => Command::Score(ScoreType::Chance),
-
-                    _ => Command::NotRecognised("Invalid score type".to_string())
-                }
-            } else {
-                return Command::NotRecognised("No score tpye found".to_string());
-            }
-            
+        "sort" => Command::Sort,
+        "hold" if input.len() < 2 => Command::NotRecognised("No dice to hold found".to_string()),
+        "hold" => toggle_command(&input[1..], rules),
+        "score" => match input.get(1) {
+            Some(word) => match ScoreType::from_word(word, rules) {
+                Some(score_type) => Command::Score(score_type),
+                None => Command::NotRecognised("Invalid score type".to_string()),
+            },
+            None => Command::NotRecognised("No score type found".to_string()),
         },
         "help" => {
             if let Some(arg) = input.get(1) {
-                match *arg {
-                    "roll" => Command::Help("roll: rolls the dice that aren't held. Counts as a roll!".to_string()),
-                    "sort" => Command::Help("sort: sorts the dice lowest to highest. Clears held dice".to_string()),
-                    "hold" => Command::Help("hold <dice>: holds dice number <dice> exluding it from next rolls".to_string()),
-                    "score" => Command::Help("score <type>: submits dice to score where <type> is the number of that score type".to_string()),
-                    "new" => Command::Help("new: starts a new game, refreshing the scores".to_string()),
-                    "quit" => Command::Help("quit: quits the game".to_string()),
-                    "help" => Command::Help("help <command>: shows possible commands or help for <command> (but you know that...)".to_string()),
+                match find_command(&arg.to_lowercase()) {
+                    Some(info) if !info.hidden => Command::Help(format!("{}: {}", info.usage, info.help)),
                     _ => Command::NotRecognised("No help found for that".to_string())
                 }
             } else {
-                return Command::Help("commands: roll, sort, hold <dice>, score <type>, new, quit, help <command>".to_string());
+                let usages: Vec<&str> = COMMANDS.iter().filter(|info| !info.hidden).map(|info| info.usage).collect();
+                Command::Help(format!("commands: {}", usages.join(", ")))
             }
         }
+        "scores" => Command::AllScores,
+        "chase" => Command::ChaseBonus,
+        "preview" => Command::Preview,
+        "scratch" => match input.get(1) {
+            Some(word) => match ScoreType::from_word(word, rules) {
+                Some(score_type) => Command::Scratch(score_type),
+                None => Command::NotRecognised("Invalid score type".to_string()),
+            },
+            None => Command::NotRecognised("No score type found".to_string()),
+        },
+        "want" => match input.get(1) {
+            Some(word) => match ScoreType::from_word(word, rules) {
+                Some(score_type) => Command::Want(score_type),
+                None => Command::NotRecognised("Invalid score type".to_string()),
+            },
+            None => Command::NotRecognised("No score type found".to_string()),
+        },
+        "replay" => match input.get(1).map(|seed| seed.parse::<u64>()) {
+            Some(Ok(seed)) => Command::Replay(Some(seed)),
+            Some(Err(_)) => Command::NotRecognised("Replay seed should be a number".to_string()),
+            None => Command::Replay(None),
+        },
+        "coach" => Command::Coach,
+        "bonusodds" => Command::BonusOdds,
+        "info" => match input.get(1) {
+            Some(word) => match ScoreType::from_word(word, rules) {
+                Some(score_type) => Command::Info(Some(score_type)),
+                None => Command::NotRecognised("Invalid score type".to_string()),
+            },
+            None => Command::Info(None),
+        },
+        "odds" => match input.get(1) {
+            Some(word) => match ScoreType::from_word(word, rules) {
+                Some(score_type) => Command::Odds(score_type),
+                None => Command::NotRecognised("Invalid score type".to_string()),
+            },
+            None => Command::NotRecognised("No score type found".to_string()),
+        },
+        "hints" => match input.get(1).map(|level| level.to_lowercase()).as_deref() {
+            Some("full") => Command::Hints(Some(HintLevel::Full)),
+            Some("brief") => Command::Hints(Some(HintLevel::Brief)),
+            Some("off") => Command::Hints(Some(HintLevel::Off)),
+            Some("auto") => Command::Hints(None),
+            Some(_) => Command::NotRecognised("Hint level should be full, brief, off or auto".to_string()),
+            None => Command::NotRecognised("No hint level found".to_string()),
+        },
+        "commands" => Command::Commands,
+        "save" => match input.get(1) {
+            Some(name) if is_valid_slot_name(name) => Command::Save(name.to_string(), input.get(2).map(|note| note.to_string())),
+            Some(_) => Command::NotRecognised("Save names can only use letters, numbers, '-' and '_'".to_string()),
+            None => Command::NotRecognised("No save name found".to_string()),
+        },
+        "saves" => Command::Saves,
+        "leaderboard" => Command::Leaderboard,
+        "stats" => Command::Stats,
+        "export" => match input.get(1) {
+            Some(path) => Command::Export(path.to_string()),
+            None => Command::NotRecognised("No file to export to found".to_string()),
+        },
+        "load" => match input.get(1) {
+            Some(name) if is_valid_slot_name(name) => Command::Load(name.to_string()),
+            Some(_) => Command::NotRecognised("Save names can only use letters, numbers, '-' and '_'".to_string()),
+            None => Command::NotRecognised("No save name found".to_string()),
+        },
+        "verify" => Command::Verify,
+        "unhold" => Command::ClearHolds,
+        "record" => match input.get(1) {
+            Some(path) => Command::Record(path.to_string()),
+            None => Command::NotRecognised("No file to record to found".to_string()),
+        },
+        "playback" => match input.get(1) {
+            Some(path) => Command::Playback(path.to_string()),
+            None => Command::NotRecognised("No recording to play back found".to_string()),
+        },
         "new" => Command::New,
+        "daily" => Command::Daily,
+        "theme" => match input.get(1) {
+            Some(name) => Command::Theme(name.to_lowercase()),
+            None => Command::NotRecognised(format!("No theme found, try {}", THEME_NAMES.join(", "))),
+        },
         "quit" => Command::Quit,
+        "repeat" => Command::Repeat,
+        "undo" => Command::Undo,
+        "yes" => Command::Confirm,
+        "no" => Command::Cancel,
 
         _ => Command::NotRecognised("Invalid command, try 'help' for list of commands".to_string()),
 
@@ -660,99 +1031,361 @@ fn parse_command_from_input(input: Vec<&str>) -> Command {
     
 }
 
-fn upper(roll: &Roll, n: u8) -> u8 {
-    let mut x = 0;
-    for i in roll.dice {
-        if i == n {
-            x += n;
-        }
+// pause before each computer move so it can be followed
+const AI_DELAY: time::Duration = time::Duration::from_millis(700);
+
+// and before each move of a recording being played back
+const PLAYBACK_DELAY: time::Duration = time::Duration::from_millis(300);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ui::{commands_panel, legend_lines, saves_panel};
+
+    fn config(args: &[&str]) -> Result<Config, String> {
+        Config::from_args(args.iter().map(|arg| arg.to_string()))
     }
-    x
-}
 
-fn evaluate_score(roll: &Roll, score_type: &ScoreType) -> u8 {
-
-    let result = match score_type {
-        ScoreType::Aces => upper(roll, 1),
-        ScoreType::Twos => upper(roll, 2),
-        ScoreType::Threes => upper(roll, 3),
-        ScoreType::Fours => upper(roll, 4),
-        ScoreType::Fives => upper(roll, 5),
-        ScoreType::Sixes => upper(roll, 6),
-
-        ScoreType::FourOfKind => {
-            match roll.dice {
-                [1, 1, 1, 1, _] => 4,
-                [_, 2, 2, 2, 2] => 8,
-                [2, 2, 2, 2, _] => 8,
-                [_, 3, 3, 3, 3] => 12,
-                [3, 3, 3, 3, _] => 12,
-                [_, 4, 4, 4, 4] => 16,
-                [4, 4, 4, 4, _] => 16,
-                [_, 5, 5, 5, 5] => 20,
-                [5, 5, 5, 5, _] => 20,
-                [_, 6, 6, 6, 6] => 24,
-                _ => 0,
-            }
-        }
-        ScoreType::FullHouse => {
-            //count the number of duplicates
-            let mut first = 0;
-            let mut second = 0;
-            for i in 1..=6 {
-                let mut amount = 0;
-                for x in roll.dice {
-                    if x == i {
-                        amount += 1;
-                    }
-                }
+    // a file under the system temp directory for one test, gone before the test starts
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("yacht_test_{}_{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
 
-                if amount == 3 {
-                    first = i;
+    #[test]
+    fn continue_loads_the_autosave() {
+        let config = GameOptions { seed: Some(7), ..GameOptions::default() };
+        let path = temp_path("continue.json");
+        let mut saved = Game::new(&config);
+        saved.attempt_command(&Command::Roll).unwrap();
+        saved.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        write_atomically(&path, &saved.to_json().to_string()).unwrap();
+
+        let (game, unreadable) = continue_game(&path, &GameOptions::default());
+        assert!(!unreadable);
+        assert_eq!(game.msg, "Continuing your autosaved game");
+        assert!(game.status_line().starts_with("Seed 7 "));
+        assert_eq!(game.current_roll.dice, saved.current_roll.dice);
+        assert_eq!(game.player().score_table.table, saved.player().score_table.table);
+        let _ = fs::remove_file(&path);
+    }
 
-                    //println!("There are 3 {}s", i);
-                }
+    #[test]
+    fn continue_without_an_autosave_starts_fresh() {
+        let (game, unreadable) = continue_game(&temp_path("missing.json"), &GameOptions::default());
+        assert!(!unreadable);
+        assert_eq!(game.msg, "No autosave found, starting a new game");
+        assert!(game.player().score_table.table.is_empty());
+    }
+
+    #[test]
+    fn continue_keeps_an_unreadable_autosave() {
+        let path = temp_path("corrupt.json");
+        fs::write(&path, "{\"broken").unwrap();
+
+        let (game, unreadable) = continue_game(&path, &GameOptions::default());
+        assert!(unreadable);
+        assert!(game.msg.ends_with("starting a new game with autosave off so it isn't overwritten"));
+        assert!(game.msg_error);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"broken");
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn only_scoring_changes_the_cards() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        let before = score_cards(&game);
+        game.attempt_command(&Command::Roll).unwrap();
+        game.attempt_command(&Command::HoldMany(vec![DiceNum::First], vec![])).unwrap();
+        assert!(score_cards(&game) == before);
+
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        assert!(score_cards(&game) != before);
+    }
 
-                if amount == 2 {
-                    second = i;
-                    //println!("There are 2 {}s", i);
+    #[test]
+    fn autosaver_writes_the_game() {
+        let path = temp_path("autosave.json");
+        let config = GameOptions { seed: Some(7), ..GameOptions::default() };
+        let mut game = Game::new(&config);
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+
+        let mut autosaver = Autosaver::new(path.clone());
+        autosaver.save(&game);
+        autosaver.finish();
+        assert_eq!(autosaver.last_error(), None);
+
+        let loaded = Game::load(&path, &config).unwrap();
+        assert_eq!(loaded.player().score_table.table, game.player().score_table.table);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn dice_colour_flags() {
+        let colours = config(&["--held-color", "cyan", "--free-color", "yellow"]).unwrap();
+        assert_eq!((colours.free_color, colours.held_color), (Color::Yellow, Color::Cyan));
+        assert!(Config::default().held_color != Config::default().free_color);
+        assert!(config(&["--held-color", "purple"]).is_err());
+    }
+
+    #[test]
+    fn title_is_set_when_the_turn_changes() {
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        let mut shown = String::new();
+        let mut output = Vec::new();
+
+        update_title(&mut output, &game, &mut shown);
+        assert_eq!(String::from_utf8(output.clone()).unwrap(), "\x1b]0;Yacht Dice — Turn 1\x07");
+
+        // nothing new to say while the turn goes on
+        output.clear();
+        game.attempt_command(&Command::Roll).unwrap();
+        update_title(&mut output, &game, &mut shown);
+        assert!(output.is_empty());
+
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        update_title(&mut output, &game, &mut shown);
+        assert_eq!(String::from_utf8(output).unwrap(), "\x1b]0;Yacht Dice — Turn 2\x07");
+    }
+
+    #[test]
+    fn every_command_is_listed() {
+        let listing = commands_panel().join("\n");
+        for info in COMMANDS {
+            for word in std::iter::once(&info.name).chain(info.aliases) {
+                let parsed = parse_command_from_input(vec![word], &Rules::default());
+                assert!(!matches!(&parsed, Command::NotRecognised(msg) if msg.starts_with("Invalid command")), "{} isn't parsed", word);
+                if !info.hidden {
+                    assert!(listing.contains(word), "{} isn't listed", word);
                 }
             }
-
-            if first == 0 || second == 0 {
-                return 0;
+            if !info.hidden {
+                assert!(listing.contains(info.usage), "{} isn't listed", info.usage);
             }
+        }
+        assert!(!listing.contains("verify"));
+    }
 
-            if first != second {
-                return 25;
-            }
+    #[test]
+    fn very_long_lines_are_refused() {
+        // a small buffer so the long line comes in many pieces, like a paste would
+        let input = format!("{}\nroll\n", "x".repeat(4 * 1024 * 1024));
+        let mut reader = io::BufReader::with_capacity(8 * 1024, input.as_bytes());
+
+        let command = command_from_line(read_capped_line(&mut reader), &Rules::default());
+        assert!(matches!(command, Command::NotRecognised(msg) if msg.starts_with("Input too long")));
+        // the rest of the long line is gone and the next one reads as normal
+        assert!(matches!(command_from_line(read_capped_line(&mut reader), &Rules::default()), Command::Roll));
+        assert!(matches!(read_capped_line(&mut reader), InputLine::Closed));
+    }
 
-            0
-        },
-        ScoreType::LittleStraight => match roll.dice {
-            [1, 2, 3, 4, 5] => 30,
-            _ => 0,
-        },
-        ScoreType::BigStraight => {
-            if roll.dice == [2, 3, 4, 5, 6] {
-                30
-            } else {
-                0
-            }
+    #[test]
+    fn line_at_the_limit_is_kept() {
+        let input = format!("{}\n", "x".repeat(MAX_INPUT_LEN));
+        assert!(matches!(read_capped_line(&mut input.as_bytes()), InputLine::Line(line) if line.trim_end().len() == MAX_INPUT_LEN));
+    }
+
+    #[test]
+    fn save_keeps_its_note() {
+        let words = split_words("save comeback \"great comeback\"\n").unwrap();
+        let command = parse_command_from_input(words.iter().map(String::as_str).collect(), &Rules::default());
+        let Command::Save(name, Some(note)) = &command else { panic!("{:?} isn't a save with a note", command) };
+        assert_eq!((name.as_str(), note.as_str()), ("comeback", "great comeback"));
+
+        let dir = temp_path("saves");
+        let _ = fs::remove_dir_all(&dir);
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&Command::Score(ScoreType::Chance)).unwrap();
+        game.save_to(&dir, name, Some(note)).unwrap();
+
+        let listed = saves_panel(&dir);
+        let loaded = Game::load(&slot_path(&dir, name), &GameOptions::default());
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(listed, ["SAVED GAMES".to_string(), format!("{:<16}great comeback", name)]);
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.player().score_table.table, game.player().score_table.table);
+        assert_eq!(loaded.current_roll.dice, game.current_roll.dice);
+    }
+
+
+    #[test]
+    fn hold_takes_several_dice() {
+        let rules = Rules::default();
+        assert_eq!(parse_command_from_input(vec!["hold", "1", "3", "5"], &rules), Command::HoldMany(vec![DiceNum::First, DiceNum::Third, DiceNum::Fifth], vec![]));
+
+        // the die that's out of range is reported, the good one still toggles
+        let command = parse_command_from_input(vec!["hold", "2", "9"], &rules);
+        assert_eq!(command, Command::HoldMany(vec![DiceNum::Second], vec!["9".to_string()]));
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        assert_eq!(game.attempt_command(&command), Ok("Toggled dice 2, ignored 9 (dice are 1-5)".to_string()));
+        assert_eq!(game.current_roll.holds, [false, true, false, false, false]);
+    }
+
+    #[test]
+    fn hold_toggles_however_many_dice_are_named() {
+        let rules = Rules::default();
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        let mut hold = |args: &[&str]| {
+            let command = parse_command_from_input([&["hold"], args].concat(), &rules);
+            game.attempt_command(&command).map(|_| game.current_roll.holds.clone())
+        };
+
+        // one die follows the same rule as several, naming a held die again lets it go
+        assert_eq!(hold(&["3"]), Ok(vec![false, false, true, false, false]));
+        assert_eq!(hold(&["3", "5"]), Ok(vec![false, false, false, false, true]));
+        assert_eq!(hold(&["5"]), Ok(vec![false; 5]));
+        assert_eq!(parse_command_from_input(vec!["hold"], &rules), Command::NotRecognised("No dice to hold found".to_string()));
+    }
+
+    #[test]
+    fn keys_map_to_commands() {
+        let rules = Rules::default();
+        let key = |code| command_for_key(&KeyEvent::new(code, KeyModifiers::NONE), &rules);
+        assert_eq!(key(KeyCode::Char('r')), Some(Command::Roll));
+        assert_eq!(key(KeyCode::Char('s')), Some(Command::Sort));
+        assert_eq!(key(KeyCode::Char('3')), Some(Command::HoldMany(vec![DiceNum::Third], Vec::new())));
+        assert_eq!(key(KeyCode::Char('.')), Some(Command::Repeat));
+        assert_eq!(key(KeyCode::Esc), Some(Command::Quit));
+        // only as many digits as there are dice
+        assert_eq!(key(KeyCode::Char('6')), None);
+        assert_eq!(key(KeyCode::Char('x')), None);
+
+        assert_eq!(command_for_key(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL), &rules), Some(Command::Quit));
+        assert_eq!(command_for_key(&KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL), &rules), None);
+    }
+
+    #[test]
+    fn unhold_lets_go_of_every_die() {
+        let rules = Rules::default();
+        assert_eq!(parse_command_from_input(vec!["unhold"], &rules), Command::ClearHolds);
+        assert_eq!(parse_command_from_input(vec!["uh"], &rules), Command::ClearHolds);
+
+        let mut game = Game::new(&GameOptions { seed: Some(7), ..GameOptions::default() });
+        game.attempt_command(&parse_command_from_input(vec!["hold", "1", "4"], &rules)).unwrap();
+        assert_eq!(game.current_roll.holds, [true, false, false, true, false]);
+        game.attempt_command(&Command::ClearHolds).unwrap();
+        assert_eq!(game.current_roll.holds, [false; 5]);
+    }
+
+    #[test]
+    fn history_steps_stop_at_either_end() {
+        let mut history = InputHistory { lines: Vec::new() };
+        // nothing to go back to yet
+        assert_eq!(history.step(0, true), 0);
+        assert_eq!(history.step(0, false), 0);
+
+        for line in ["roll", "roll", "hold 1", "", "score 3"] {
+            history.push(line);
         }
-        ScoreType::Yacht => {
-            let i = roll.dice[0];
-            if roll.dice.iter().all(|&x| x == i) {
-                50
-            } else {
-                0
-            }
+        assert_eq!(history.lines, ["roll", "hold 1", "score 3"]);
+
+        // up from the new line goes back through them, then stays on the oldest
+        assert_eq!(history.step(3, true), 2);
+        assert_eq!(history.step(1, true), 0);
+        assert_eq!(history.step(0, true), 0);
+        // and down comes back to the new line without going past it
+        assert_eq!(history.step(2, false), 3);
+        assert_eq!(history.step(3, false), 3);
+    }
+
+    #[test]
+    fn played_back_recording_ends_on_the_same_card() {
+        let config = GameOptions { seed: Some(7), ..GameOptions::default() };
+        let mut game = Game::new(&config);
+        let mut lines = vec!["roll".to_string(), "hold 2".to_string(), "undo".to_string()];
+        lines.extend((1..=13).flat_map(|category| ["roll".to_string(), "hold 1".to_string(), "roll".to_string(), format!("score {}", category)]));
+        for line in lines {
+            let _ = game.attempt_command(&command_from_line(InputLine::Line(line), &config.rules));
+        }
+        assert_eq!(game.game_state, GameStates::GameOver);
+
+        let path = temp_path("recording");
+        game.attempt_command(&Command::Record(path.clone())).unwrap();
+
+        // the dice come from the recording, whatever seed the game playing it back was started with
+        let (mut played, moves) = Game::load_recording(&path, &GameOptions { seed: Some(99), ..GameOptions::default() }).unwrap();
+        for line in moves {
+            let _ = played.attempt_command(&command_from_line(InputLine::Line(line), &config.rules));
         }
-        ScoreType::Chance => {
-            let sum: u8 = roll.dice.iter().sum();
-            sum
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(played.game_state, GameStates::GameOver);
+        assert!(played.players[0].score_table == game.players[0].score_table);
+        assert_eq!(played.total(), game.total());
+    }
+
+    #[test]
+    fn legend_words_are_commands() {
+        let rules = Rules::default();
+        let legend = legend_lines();
+
+        // the word and its argument fill the first 7 columns, with a 1 standing in for the N
+        let commands: Vec<Command> = legend
+            .iter()
+            .map(|line| {
+                let (input, _) = line.split_at(7);
+                parse_command_from_input(input.split_whitespace().map(|word| if word == "N" { "1" } else { word }).collect(), &rules)
+            })
+            .collect();
+        assert_eq!(commands, vec![Command::Roll, Command::Sort, Command::HoldMany(vec![DiceNum::First], vec![]), Command::Score(ScoreType::Aces), Command::New, Command::Quit]);
+        assert!(legend.iter().all(|line| line.chars().count() <= 19), "{:?}", legend);
+    }
+
+    #[test]
+    fn commands_and_keywords_in_any_case() {
+        let rules = Rules::default();
+        assert_eq!(parse_command_from_input(vec!["SCORE", "YACHT"], &rules), Command::Score(ScoreType::Yacht));
+        assert_eq!(parse_command_from_input(vec!["Score", "FullHouse"], &rules), Command::Score(ScoreType::FullHouse));
+        assert_eq!(parse_command_from_input(vec!["Roll"], &rules), Command::Roll);
+        assert_eq!(parse_command_from_input(vec!["HINTS", "Off"], &rules), Command::Hints(Some(HintLevel::Off)));
+        let help = parse_command_from_input(vec!["Help", "ROLL"], &rules);
+        assert!(matches!(&help, Command::Help(text) if text.starts_with("roll")), "{:?}", help);
+    }
+
+    #[test]
+    fn pip_flag_takes_one_visible_symbol() {
+        assert_eq!(config(&[]).unwrap().pip, 'O');
+        assert_eq!(config(&["--pip", "*"]).unwrap().pip, '*');
+        assert_eq!(config(&["--pip", "●"]).unwrap().pip, '●');
+        for pip in ["", " ", "**", "\t"] {
+            assert!(config(&["--pip", pip]).is_err(), "{:?}", pip);
         }
-    };
+        assert!(config(&["--pip"]).is_err());
+    }
+
+    #[test]
+    fn reroll_takes_the_dice_to_roll() {
+        let rules = Rules::default();
+        assert_eq!(parse_command_from_input(vec!["reroll", "1", "2", "3"], &rules), Command::ReRoll(vec![DiceNum::First, DiceNum::Second, DiceNum::Third]));
+        assert_eq!(parse_command_from_input(vec!["reroll"], &rules), Command::NotRecognised("No dice to reroll found".to_string()));
+        assert_eq!(parse_command_from_input(vec!["reroll", "2", "6"], &rules), Command::NotRecognised("Invalid Dice Number, should be (1-5)".to_string()));
+    }
+
+    #[test]
+    fn bell_is_on_unless_turned_off() {
+        assert!(config(&[]).unwrap().bell);
+        assert!(!config(&["--no-bell"]).unwrap().bell);
+    }
 
-    result
+    #[test]
+    fn end_of_input_quits() {
+        let rules = Rules::default();
+        assert!(matches!(command_from_line(read_capped_line(&mut "".as_bytes()), &rules), Command::Quit));
+
+        // a last line with no newline is still read, and only then is the input closed
+        let mut reader = "\nroll".as_bytes();
+        assert!(matches!(read_capped_line(&mut reader), InputLine::Line(line) if line.trim().is_empty()));
+        assert!(matches!(command_from_line(read_capped_line(&mut reader), &rules), Command::Roll));
+        assert!(matches!(command_from_line(read_capped_line(&mut reader), &rules), Command::Quit));
+    }
+
+    #[test]
+    fn rolls_flag_sets_the_limit() {
+        assert_eq!(config(&[]).unwrap().options.rolls_per_turn, 3);
+        assert_eq!(config(&["--rolls", "5"]).unwrap().options.rolls_per_turn, 5);
+        assert!(config(&["--rolls", "0"]).is_err());
+        assert!(config(&["--rolls"]).is_err());
+    }
 }
@@ -0,0 +1,116 @@
+use std::{fs, io};
+
+use crate::json::{self, Json};
+
+pub const AUTOSAVE_PATH: &str = "yacht_autosave.json";
+
+pub const LEADERBOARD_PATH: &str = "yacht_leaderboard.json";
+
+// how many entries the leaderboard shows
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// named saves live here as <name>.json, separate from the autosave
+pub const SAVE_DIR: &str = "yacht_saves";
+
+pub fn is_valid_slot_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn slot_path(dir: &str, name: &str) -> String {
+    format!("{}/{}.json", dir, name)
+}
+
+// each game saved in dir with the note it was saved with, in name order
+pub fn saved_slots(dir: &str) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+
+    let mut slots: Vec<(String, String)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().strip_suffix(".json")?.to_string();
+            let note = fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|contents| json::parse(&contents).ok())
+                .and_then(|save| save.get("note").and_then(Json::as_str).map(str::to_string))
+                .unwrap_or_default();
+            Some((name, note))
+        })
+        .collect();
+    slots.sort();
+    slots
+}
+
+// every recorded score, a missing file just means nothing has been recorded yet
+pub fn load_leaderboard(path: &str) -> Result<Vec<(String, u16)>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Couldn't read {}: {}", path, e)),
+    };
+    let json = json::parse(&contents).map_err(|e| format!("{} is not a valid leaderboard: {}", path, e))?;
+
+    json.as_array()
+        .ok_or(format!("{} is not a valid leaderboard", path))?
+        .iter()
+        .map(|entry| {
+            let name = entry.get("name").and_then(Json::as_str);
+            let score = entry.get("score").and_then(Json::as_u64).and_then(|num| u16::try_from(num).ok());
+            name.zip(score).map(|(name, score)| (name.to_string(), score)).ok_or(format!("{} has an invalid entry", path))
+        })
+        .collect()
+}
+
+pub fn record_scores(path: &str, new_entries: &[(String, u16)]) -> Result<(), String> {
+    let mut entries = load_leaderboard(path)?;
+    entries.extend_from_slice(new_entries);
+
+    let json = Json::Array(
+        entries
+            .into_iter()
+            .map(|(name, score)| Json::Object(vec![("name".to_string(), Json::Str(name)), ("score".to_string(), Json::number(score))]))
+            .collect(),
+    );
+    write_atomically(path, &json.to_string())
+}
+
+// highest first, equal scores keep the order they were recorded in
+pub fn top_scores(mut entries: Vec<(String, u16)>, count: usize) -> Vec<(String, u16)> {
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.1));
+    entries.truncate(count);
+    entries
+}
+
+// writes to a sibling temp file first so a crash mid-write never leaves a half written save behind
+pub fn write_atomically(path: &str, contents: &str) -> Result<(), String> {
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, contents)
+        .and_then(|_| fs::rename(&tmp_path, path))
+        .map_err(|e| format!("Couldn't save to {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaderboard_keeps_the_top_ten() {
+        let path = std::env::temp_dir().join(format!("yacht_test_{}_leaderboard.json", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = fs::remove_file(&path);
+        assert_eq!(load_leaderboard(&path), Ok(Vec::new()));
+
+        let first: Vec<(String, u16)> = (1..=6).map(|i| (format!("p{}", i), i * 20)).collect();
+        let second: Vec<(String, u16)> = (7..=12).map(|i| (format!("p{}", i), 240 - i * 10)).collect();
+        record_scores(&path, &first).unwrap();
+        record_scores(&path, &second).unwrap();
+        let entries = load_leaderboard(&path);
+        let _ = fs::remove_file(&path);
+
+        let entries = entries.unwrap();
+        assert_eq!(entries.len(), 12);
+        let top = top_scores(entries, LEADERBOARD_SIZE);
+        let top: Vec<(&str, u16)> = top.iter().map(|(name, score)| (name.as_str(), *score)).collect();
+        // p6 and p12 both have 120 and stay in the order they were recorded
+        assert_eq!(top, [("p7", 170), ("p8", 160), ("p9", 150), ("p10", 140), ("p11", 130), ("p6", 120), ("p12", 120), ("p5", 100), ("p4", 80), ("p3", 60)]);
+    }
+}
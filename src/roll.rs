@@ -0,0 +1,201 @@
+use rand::Rng;
+
+use crate::score::{Rules, ScoreType};
+
+#[derive(Clone, PartialEq)]
+pub struct Roll {
+    pub dice: Vec<u8>,
+    pub holds: Vec<bool>,
+    // the highest face, 6 unless --sides says otherwise
+    pub sides: u8,
+}
+
+impl Roll {
+    pub fn new(rng: &mut impl Rng, rules: &Rules) -> Self {
+        Roll {
+            dice: Roll::gen_roll(rng, rules.dice, rules.sides),
+            holds: vec![false; rules.dice as usize],
+            sides: rules.sides,
+        }
+    }
+
+    // dice that were never rolled, like the ones scorecard checks and saves are built from
+    pub fn from_dice(dice: Vec<u8>, sides: u8) -> Self {
+        Roll { holds: vec![false; dice.len()], dice, sides }
+    }
+
+    // from_dice for faces that came from outside the game, a 0 or anything over sides is refused here rather
+    // than scored as nothing and drawn as a '?'
+    pub fn try_from_dice(dice: Vec<u8>, sides: u8) -> Result<Self, String> {
+        match dice.iter().find(|&&die| !(1..=sides).contains(&die)) {
+            Some(die) => Err(format!("{} isn't a face of a {}-sided die", die, sides)),
+            None => Ok(Roll::from_dice(dice, sides)),
+        }
+    }
+
+    // holds move with their dice, so a held die is still held wherever it ends up
+    pub fn sort(&mut self) {
+        let mut pairs: Vec<(u8, bool)> = self.dice.iter().copied().zip(self.holds.iter().copied()).collect();
+        pairs.sort_by_key(|&(die, _)| die);
+        for (i, (die, held)) in pairs.into_iter().enumerate() {
+            self.dice[i] = die;
+            self.holds[i] = held;
+        }
+    }
+
+    pub fn reset_holds(&mut self) {
+        self.holds.fill(false);
+    }
+
+    fn gen_roll(rng: &mut impl Rng, count: u8, sides: u8) -> Vec<u8> {
+        (0..count).map(|_| rng.gen_range(1..=sides)).collect()
+    }
+
+    // returns the faces that were actually rolled, held dice aren't included
+    pub fn roll_with_holds(&mut self, rng: &mut impl Rng) -> Vec<u8> {
+        let mut rolled = Vec::new();
+        for i in 0..self.dice.len() {
+            if !self.holds[i] {
+                self.dice[i] = rng.gen_range(1..=self.sides);
+                rolled.push(self.dice[i]);
+            }
+        }
+        rolled
+    }
+
+    pub fn count_face(&self, face: u8) -> u8 {
+        self.dice.iter().filter(|&&x| x == face).count() as u8
+    }
+
+    // how many of each face is showing, indexed by face so index 0 is unused
+    pub fn face_counts(&self) -> Vec<u8> {
+        let mut counts = vec![0; self.sides as usize + 1];
+        for &x in &self.dice {
+            counts[x as usize] += 1;
+        }
+        counts
+    }
+
+    pub fn hold_face(&mut self, face: u8) {
+        self.holds = self.dice.iter().map(|&x| x == face).collect();
+    }
+
+    pub fn toggle_hold(&mut self, num: &DiceNum) {
+        self.holds[*num as usize] = !self.holds[*num as usize];
+    }
+
+    // the dice that count towards the category, or for the multiple of a kind categories the most common face
+    // (ties going to the higher face) since that's what they'd be built from
+    pub fn wanted_dice(&self, score_type: &ScoreType) -> Vec<bool> {
+        let counts = self.face_counts();
+        let most_common = (1..=self.sides).max_by_key(|&face| (counts[face as usize], face)).unwrap_or(self.sides);
+
+        match score_type {
+            ScoreType::ThreeOfKind | ScoreType::FourOfKind | ScoreType::Yacht => self.dice.iter().map(|&x| x == most_common).collect(),
+            ScoreType::FullHouse => self.dice.iter().map(|&x| counts[x as usize] >= 2).collect(),
+            ScoreType::LittleStraight | ScoreType::BigStraight | ScoreType::Straight => {
+                // a plain straight goes for whichever run the dice are closer to
+                let closer_to_low = (1..=5).filter(|&face| counts[face] > 0).count() > (2..=6).filter(|&face| counts[face] > 0).count();
+                let wanted = if *score_type == ScoreType::LittleStraight || (*score_type == ScoreType::Straight && closer_to_low) { 1..=5 } else { 2..=6 };
+                let mut seen = vec![false; self.sides as usize + 1];
+                self.dice
+                    .iter()
+                    .map(|&x| {
+                        let first = wanted.contains(&x) && !seen[x as usize];
+                        seen[x as usize] = true;
+                        first
+                    })
+                    .collect()
+            },
+            ScoreType::Chance => vec![true; self.dice.len()],
+            upper => self.dice.iter().map(|&x| Some(x) == upper.face()).collect(),
+        }
+    }
+
+    // a plain strategy for going after one category: the dice that count towards it, except for Chance where
+    // only the high faces are worth keeping
+    pub fn suggested_holds(&self, score_type: &ScoreType) -> Vec<bool> {
+        match score_type {
+            ScoreType::Chance => self.dice.iter().map(|&x| x > self.sides / 2).collect(),
+            _ => self.wanted_dice(score_type),
+        }
+    }
+
+    // used by the computer and when replaying decisions against other dice
+    pub fn hold_for(&mut self, score_type: &ScoreType) {
+        self.holds = self.suggested_holds(score_type);
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DiceNum {
+    First = 0,
+    Second = 1,
+    Third = 2,
+    Fourth = 3,
+    Fifth = 4,
+    Sixth = 5,
+}
+
+impl DiceNum {
+    // dice as the player numbers them, 1 up to the number of dice in play
+    pub fn from_number(num: u8, dice: u8) -> Option<DiceNum> {
+        if num > dice {
+            return None;
+        }
+        match num {
+            1 => Some(DiceNum::First),
+            2 => Some(DiceNum::Second),
+            3 => Some(DiceNum::Third),
+            4 => Some(DiceNum::Fourth),
+            5 => Some(DiceNum::Fifth),
+            6 => Some(DiceNum::Sixth),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wanted(dice: Vec<u8>, score_type: ScoreType) -> Vec<usize> {
+        let roll = Roll::try_from_dice(dice, 6).unwrap();
+        roll.wanted_dice(&score_type).iter().enumerate().filter(|(_, &held)| held).map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn dice_each_category_wants() {
+        assert_eq!(wanted(vec![4, 2, 4, 6, 4], ScoreType::Fours), [0, 2, 4]);
+        assert_eq!(wanted(vec![4, 2, 4, 6, 4], ScoreType::Threes), Vec::<usize>::new());
+        // the most common face, the higher one on a tie
+        assert_eq!(wanted(vec![2, 5, 2, 5, 1], ScoreType::FourOfKind), [1, 3]);
+        assert_eq!(wanted(vec![3, 3, 1, 1, 6], ScoreType::FullHouse), [0, 1, 2, 3]);
+        // one of each face in the run, a repeated face only once
+        assert_eq!(wanted(vec![2, 3, 3, 5, 1], ScoreType::LittleStraight), [0, 1, 3, 4]);
+        assert_eq!(wanted(vec![2, 3, 3, 5, 1], ScoreType::BigStraight), [0, 1, 3]);
+        assert_eq!(wanted(vec![1, 4, 6, 3, 5], ScoreType::Chance), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn sorting_keeps_held_dice_held() {
+        let mut roll = Roll::try_from_dice(vec![6, 2, 5, 1, 2], 6).unwrap();
+        roll.toggle_hold(&DiceNum::First);
+        roll.toggle_hold(&DiceNum::Fifth);
+        roll.sort();
+
+        assert_eq!(roll.dice, [1, 2, 2, 5, 6]);
+        // the held 6 moves to the end, and the held 2 keeps its place after the other 2
+        assert_eq!(roll.holds, [false, false, true, false, true]);
+    }
+
+    #[test]
+    fn only_faces_of_the_die_are_accepted() {
+        let roll = Roll::try_from_dice(vec![1, 2, 3, 4, 6], 6).unwrap();
+        assert_eq!(roll.dice, vec![1, 2, 3, 4, 6]);
+        assert!(Roll::try_from_dice(vec![9, 9, 1, 5, 5, 5], 9).is_ok());
+
+        assert_eq!(Roll::try_from_dice(vec![0, 2, 3, 4, 5], 6).err(), Some("0 isn't a face of a 6-sided die".to_string()));
+        assert_eq!(Roll::try_from_dice(vec![1, 2, 7, 4, 5], 6).err(), Some("7 isn't a face of a 6-sided die".to_string()));
+    }
+}
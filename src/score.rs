@@ -0,0 +1,1263 @@
+use std::collections::HashMap;
+
+use crate::{json::Json, roll::Roll};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum ScoreType {
+    Aces,
+    Twos,
+    Threes,
+    Fours,
+    Fives,
+    Sixes,
+    FourOfKind,
+    FullHouse,
+    LittleStraight,
+    BigStraight,
+    Yacht,
+    Chance,
+    ThreeOfKind,
+    Straight,
+}
+
+impl ScoreType {
+    // every category in any ruleset, Rules::categories has the ones in play
+    pub fn all() -> [ScoreType; 14] {
+        [
+            ScoreType::Aces,
+            ScoreType::Twos,
+            ScoreType::Threes,
+            ScoreType::Fours,
+            ScoreType::Fives,
+            ScoreType::Sixes,
+            ScoreType::FourOfKind,
+            ScoreType::FullHouse,
+            ScoreType::LittleStraight,
+            ScoreType::BigStraight,
+            ScoreType::Yacht,
+            ScoreType::Chance,
+            ScoreType::ThreeOfKind,
+            ScoreType::Straight,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScoreType::Aces => "Aces",
+            ScoreType::Twos => "Twos",
+            ScoreType::Threes => "Threes",
+            ScoreType::Fours => "Fours",
+            ScoreType::Fives => "Fives",
+            ScoreType::Sixes => "Sixes",
+            ScoreType::FourOfKind => "Four Of A Kind",
+            ScoreType::FullHouse => "Full House",
+            ScoreType::LittleStraight => "Little Straight",
+            ScoreType::BigStraight => "Big Straight",
+            ScoreType::Yacht => "Yacht",
+            ScoreType::Chance => "Chance",
+            ScoreType::ThreeOfKind => "Three Of A Kind",
+            ScoreType::Straight => "Straight",
+        }
+    }
+
+    // the die face an upper section category counts, None for the lower section
+    pub fn face(&self) -> Option<u8> {
+        match self {
+            ScoreType::Aces => Some(1),
+            ScoreType::Twos => Some(2),
+            ScoreType::Threes => Some(3),
+            ScoreType::Fours => Some(4),
+            ScoreType::Fives => Some(5),
+            ScoreType::Sixes => Some(6),
+            _ => None,
+        }
+    }
+
+    // the most the category can ever score under the given rules
+    pub fn max_score(&self, rules: &Rules) -> u8 {
+        let generala = rules.ruleset == Ruleset::Generala;
+        let all_high = rules.dice * rules.sides;
+        match self {
+            ScoreType::FourOfKind => if generala { 40 } else { rules.sides * 4 },
+            ScoreType::FullHouse => if generala { 30 } else { 25 },
+            ScoreType::Straight => 20,
+            ScoreType::LittleStraight | ScoreType::BigStraight => 30,
+            ScoreType::Yacht => 50,
+            ScoreType::Chance => rules.chance_cap.unwrap_or(all_high).min(all_high),
+            ScoreType::ThreeOfKind => all_high,
+            upper => upper.face().unwrap_or(0) * rules.dice,
+        }
+    }
+
+    // how the category scores and what it's worth, short enough for a line of the info panel
+    pub fn rule(&self, rules: &Rules) -> String {
+        let in_order = if rules.strict_order { " in order" } else { "" };
+        let max = self.max_score(rules);
+        match self {
+            ScoreType::FourOfKind if rules.ruleset == Ruleset::Generala => format!("four alike{}, {}", in_order, max),
+            ScoreType::FourOfKind => format!("four alike{}, those four summed, max {}", in_order, max),
+            ScoreType::FullHouse => format!("three alike and two alike, {}", max),
+            ScoreType::LittleStraight => format!("1-2-3-4-5{}, {}", in_order, max),
+            ScoreType::BigStraight => format!("2-3-4-5-6{}, {}", in_order, max),
+            ScoreType::Straight => format!("1-2-3-4-5 or 2-3-4-5-6{}, {}", in_order, max),
+            ScoreType::Yacht => format!("all {} alike, {}", rules.dice, max),
+            ScoreType::Chance => format!("any dice, all summed, max {}", max),
+            ScoreType::ThreeOfKind => format!("three alike, all dice summed, max {}", max),
+            upper => format!("the {}s summed, max {}", upper.face().unwrap_or(0), max),
+        }
+    }
+
+    // stable name used in save files, matches the score command's word for it
+    pub fn key(&self) -> &'static str {
+        match self {
+            ScoreType::Aces => "aces",
+            ScoreType::Twos => "twos",
+            ScoreType::Threes => "threes",
+            ScoreType::Fours => "fours",
+            ScoreType::Fives => "fives",
+            ScoreType::Sixes => "sixes",
+            ScoreType::FourOfKind => "fourofakind",
+            ScoreType::FullHouse => "fullhouse",
+            ScoreType::LittleStraight => "littlestraight",
+            ScoreType::BigStraight => "bigstraight",
+            ScoreType::Yacht => "yacht",
+            ScoreType::Chance => "chance",
+            ScoreType::ThreeOfKind => "threeofakind",
+            ScoreType::Straight => "straight",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ScoreType> {
+        ScoreType::all().into_iter().find(|score_type| score_type.key() == key)
+    }
+
+    // the category at this row of the table, counting from 0. The rows depend on the rules, and None past the last one
+    pub fn from_index(index: u8, rules: &Rules) -> Option<ScoreType> {
+        rules.categories().get(index as usize).copied()
+    }
+
+    // a category as the player types it, either its number on the table or its name, as long as it's in play
+    pub fn from_word(word: &str, rules: &Rules) -> Option<ScoreType> {
+        let from_number = word.parse::<u8>().ok().and_then(|num| num.checked_sub(1)).and_then(|index| ScoreType::from_index(index, rules));
+        from_number.or(parse_score_type(word).filter(|score_type| rules.categories().contains(score_type)))
+    }
+}
+
+// short names that aren't just the start of a key
+const SCORE_ALIASES: [(&str, ScoreType); 16] = [
+    ("fh", ScoreType::FullHouse),
+    ("house", ScoreType::FullHouse),
+    ("ls", ScoreType::LittleStraight),
+    ("lstraight", ScoreType::LittleStraight),
+    ("small", ScoreType::LittleStraight),
+    ("smallstraight", ScoreType::LittleStraight),
+    ("bs", ScoreType::BigStraight),
+    ("bstraight", ScoreType::BigStraight),
+    ("large", ScoreType::BigStraight),
+    ("largestraight", ScoreType::BigStraight),
+    ("4k", ScoreType::FourOfKind),
+    ("4oak", ScoreType::FourOfKind),
+    ("quads", ScoreType::FourOfKind),
+    ("3k", ScoreType::ThreeOfKind),
+    ("3oak", ScoreType::ThreeOfKind),
+    ("yahtzee", ScoreType::Yacht),
+];
+
+// a category from its key, one of the aliases above, or the start of exactly one key, so 'full',
+// 'little', 'big', 'y' and 'ch' all work but 'four' (fours or fourofakind) doesn't
+fn parse_score_type(word: &str) -> Option<ScoreType> {
+    let word = word.to_lowercase();
+    if word.is_empty() {
+        return None;
+    }
+    if let Some(score_type) = ScoreType::from_key(&word) {
+        return Some(score_type);
+    }
+    if let Some(&(_, score_type)) = SCORE_ALIASES.iter().find(|(alias, _)| *alias == word) {
+        return Some(score_type);
+    }
+
+    let mut matches = ScoreType::all().into_iter().filter(|score_type| score_type.key().starts_with(&word));
+    match (matches.next(), matches.next()) {
+        (Some(score_type), None) => Some(score_type),
+        _ => None,
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ScoreTable {
+    pub table: HashMap<ScoreType, u8>,
+    pub turn_filled: HashMap<ScoreType, u8>,
+    // 100 for every yacht rolled after the Yacht box was already scored for 50
+    pub yacht_bonus: u16,
+}
+
+impl ScoreTable {
+    pub fn new() -> Self {
+        ScoreTable { table: HashMap::new(), turn_filled: HashMap::new(), yacht_bonus: 0 }
+    }
+
+    pub fn reset_scores(&mut self) {
+        self.table.clear();
+        self.turn_filled.clear();
+        self.yacht_bonus = 0;
+    }
+
+    pub fn check_table(&self, score_type: &ScoreType) -> bool {
+        self.table.contains_key(score_type)
+    }
+
+    pub fn table_total(&self) -> u16 {
+        let mut sum = 0;
+        for &score in self.table.values() {
+            sum += score as u16;
+        }
+
+        sum + self.upper_bonus() + self.yacht_bonus
+    }
+
+    pub fn upper_bonus(&self) -> u16 {
+        if self.upper_total() >= UPPER_BONUS_THRESHOLD { UPPER_BONUS } else { 0 }
+    }
+
+    pub fn upper_total(&self) -> u16 {
+        ScoreType::all()
+            .iter()
+            .filter(|score_type| score_type.face().is_some())
+            .filter_map(|score_type| self.table.get(score_type))
+            .map(|&score| score as u16)
+            .sum()
+    }
+
+    // everything below the upper section, without the yacht bonus
+    pub fn lower_total(&self) -> u16 {
+        ScoreType::all()
+            .iter()
+            .filter(|score_type| score_type.face().is_none())
+            .filter_map(|score_type| self.table.get(score_type))
+            .map(|&score| score as u16)
+            .sum()
+    }
+
+    pub fn open_categories(&self, rules: &Rules) -> Vec<ScoreType> {
+        rules.categories().into_iter().filter(|score_type| !self.check_table(score_type)).collect()
+    }
+
+    // every ruleset has the whole upper section, so these don't depend on which is in play
+    pub fn open_upper_faces(&self) -> Vec<u8> {
+        ScoreType::all().iter().filter(|score_type| !self.check_table(score_type)).filter_map(ScoreType::face).collect()
+    }
+
+    // joker rules for a yacht rolled once the Yacht box is filled: the matching upper box if it's open, otherwise any
+    // open lower box, otherwise any open box at all. None when the dice aren't a joker, or the ruleset has no jokers
+    pub fn joker_categories(&self, roll: &Roll, rules: &Rules) -> Option<Vec<ScoreType>> {
+        if rules.ruleset != Ruleset::Yacht || !roll.face_counts().contains(&rules.dice) || !self.check_table(&ScoreType::Yacht) {
+            return None;
+        }
+
+        let open = self.open_categories(rules);
+        let matching_upper: Vec<ScoreType> = open.iter().copied().filter(|score_type| score_type.face() == Some(roll.dice[0])).collect();
+        let lower: Vec<ScoreType> = open.iter().copied().filter(|score_type| score_type.face().is_none()).collect();
+
+        if !matching_upper.is_empty() {
+            Some(matching_upper)
+        } else if !lower.is_empty() {
+            Some(lower)
+        } else {
+            Some(open)
+        }
+    }
+
+    // what the dice would score in the category on this card, a joker filling a straight scores it in full
+    pub fn score_for(&self, roll: &Roll, score_type: &ScoreType, rules: &Rules) -> u8 {
+        let straight = matches!(score_type, ScoreType::LittleStraight | ScoreType::BigStraight);
+        if straight && self.joker_categories(roll, rules).is_some() {
+            return score_type.max_score(rules);
+        }
+        evaluate_score(roll, score_type, rules)
+    }
+
+    pub fn score_on_table(&mut self, score_type: &ScoreType, roll: &Roll, rules: &Rules) -> bool {
+        if self.check_table(score_type) {
+            return false;
+        }
+
+        let score = self.score_for(roll, score_type, rules);
+
+        let yacht_scored = self.table.get(&ScoreType::Yacht).is_some_and(|&yacht| yacht > 0);
+        if rules.ruleset == Ruleset::Yacht && roll.face_counts().contains(&rules.dice) && yacht_scored {
+            self.yacht_bonus += YACHT_BONUS;
+        }
+
+        self.table.insert(*score_type, score);
+
+        true
+
+    }
+
+    // the finished card for export, every category by name with "unused" for any left open
+    pub fn scorecard_json(&self, rules: &Rules) -> Json {
+        let categories = rules
+            .categories()
+            .iter()
+            .map(|score_type| {
+                let score = self.table.get(score_type).map_or(Json::Str("unused".to_string()), |&score| Json::number(score));
+                (score_type.key().to_string(), score)
+            })
+            .collect();
+
+        Json::Object(vec![
+            ("categories".to_string(), Json::Object(categories)),
+            ("upper_total".to_string(), Json::number(self.upper_total())),
+            ("upper_bonus".to_string(), Json::number(self.upper_bonus())),
+            ("yacht_bonus".to_string(), Json::number(self.yacht_bonus)),
+            ("card_total".to_string(), Json::number(self.table_total())),
+        ])
+    }
+
+    // a deliberate 0 in an open category, refused like score_on_table if it's already filled
+    pub fn scratch_on_table(&mut self, score_type: &ScoreType) -> bool {
+        if self.check_table(score_type) {
+            return false;
+        }
+
+        self.table.insert(*score_type, 0);
+
+        true
+    }
+
+    pub fn get_table_value(&self, score_type: &ScoreType) -> String {
+        match self.table.get(score_type) {
+            Some(score) => format!(" {}", score),
+            None => " X ".to_string(),
+        }
+    }
+
+    fn _print_table(&self) {
+        for score_type in ScoreType::all() {
+            println!("{} - {}", score_type.name(), self.get_table_value(&score_type));
+        }
+    }
+
+    // the open category the dice would score most in. A tie goes to the higher-value category, the one with the
+    // bigger max_score, and after that to the later one in the table
+    pub fn best_open_score(&self, roll: &Roll, rules: &Rules) -> Option<(ScoreType, u16)> {
+        let joker = self.joker_categories(roll, rules);
+        self.open_categories(rules)
+            .iter()
+            .filter(|score_type| joker.as_ref().is_none_or(|allowed| allowed.contains(score_type)))
+            .map(|score_type| (*score_type, self.score_for(roll, score_type, rules) as u16))
+            .max_by_key(|&(score_type, score)| (score, score_type.max_score(rules)))
+    }
+
+    // every category with what the given dice would score in it, and the turn it was used on if it has been
+    pub fn all_scores(&self, roll: &Roll, rules: &Rules) -> Vec<(ScoreType, u8, Option<u8>)> {
+        rules
+            .categories()
+            .iter()
+            .map(|score_type| (*score_type, evaluate_score(roll, score_type, rules), self.turn_filled.get(score_type).copied()))
+            .collect()
+    }
+}
+
+// scoring variations chosen at the command line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rules {
+    // straights and four of a kind only count when the dice lie in order, so sorting is part of the game
+    pub strict_order: bool,
+    // some tables limit how much Chance can be worth
+    pub chance_cap: Option<u8>,
+    // the dice dealt at the start of a turn have to be rolled at least once before scoring
+    pub roll_first: bool,
+    pub ruleset: Ruleset,
+    // how many dice are rolled and how many sides each has, 5d6 unless --dice or --sides say otherwise
+    pub dice: u8,
+    pub sides: u8,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules { strict_order: false, chance_cap: None, roll_first: false, ruleset: Ruleset::default(), dice: 5, sides: 6 }
+    }
+}
+
+impl Rules {
+    // the categories on the table, in table order. A full house is three and two of a kind, so it's only
+    // played with five dice
+    pub fn categories(&self) -> Vec<ScoreType> {
+        let mut categories = self.ruleset.categories();
+        if self.dice != 5 {
+            categories.retain(|&score_type| score_type != ScoreType::FullHouse);
+        }
+        categories
+    }
+}
+
+// which categories are played and what the fixed value hands are worth
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Ruleset {
+    #[default]
+    Yacht,
+    // the South American game: one straight worth 20, full house 30, four of a kind 40, no Chance
+    Generala,
+}
+
+impl Ruleset {
+    pub fn from_name(name: &str) -> Option<Ruleset> {
+        match name {
+            "yacht" => Some(Ruleset::Yacht),
+            "generala" => Some(Ruleset::Generala),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ruleset::Yacht => "yacht",
+            Ruleset::Generala => "generala",
+        }
+    }
+
+    fn categories(&self) -> Vec<ScoreType> {
+        let upper = [ScoreType::Aces, ScoreType::Twos, ScoreType::Threes, ScoreType::Fours, ScoreType::Fives, ScoreType::Sixes];
+        let lower: &[ScoreType] = match self {
+            Ruleset::Yacht => &[
+                ScoreType::FourOfKind,
+                ScoreType::FullHouse,
+                ScoreType::LittleStraight,
+                ScoreType::BigStraight,
+                ScoreType::Yacht,
+                ScoreType::Chance,
+                ScoreType::ThreeOfKind,
+            ],
+            Ruleset::Generala => &[ScoreType::Straight, ScoreType::FullHouse, ScoreType::FourOfKind, ScoreType::Yacht],
+        };
+        upper.iter().chain(lower).copied().collect()
+    }
+}
+
+// every category in play with its rule, the upper section sharing a line as its rules only differ by face
+pub fn scoring_panel(rules: &Rules) -> Vec<String> {
+    let yacht_bonus = if rules.ruleset == Ruleset::Yacht { format!(", yacht bonus {}", YACHT_BONUS) } else { "".to_string() };
+    let mut lines = vec![format!("SCORING   (upper bonus {} at {}{})", UPPER_BONUS, UPPER_BONUS_THRESHOLD, yacht_bonus)];
+
+    let (upper, lower): (Vec<ScoreType>, Vec<ScoreType>) = rules.categories().into_iter().partition(|score_type| score_type.face().is_some());
+    if let (Some(first), Some(last)) = (upper.first(), upper.last()) {
+        lines.push(format!("{} to {}: that face summed, max {} to {}", first.name(), last.name(), first.max_score(rules), last.max_score(rules)));
+    }
+    for score_type in lower {
+        lines.push(format!("{}: {}", score_type.name(), score_type.rule(rules)));
+    }
+
+    lines
+}
+
+// upper section total needed for the upper bonus
+pub const UPPER_BONUS_THRESHOLD: u16 = 63;
+
+const UPPER_BONUS: u16 = 35;
+
+const YACHT_BONUS: u16 = 100;
+
+// chance of the upper bonus if every open upper category is chased on its own turn, holding that face and
+// rerolling the rest. Each die then shows the face with a fixed chance, so each category is a binomial of
+// all the dice, and the categories are combined by tracking how likely each running total is. Real play picks
+// categories to suit the dice, so this runs low early in a game and sharpens as the upper section fills
+pub fn estimate_bonus_odds(upper_total: u16, open_faces: &[u8], rolls_per_turn: u8, rules: &Rules) -> f64 {
+    let Some(needed) = UPPER_BONUS_THRESHOLD.checked_sub(upper_total).filter(|&needed| needed > 0) else {
+        return 1.0;
+    };
+    let needed = needed as usize;
+
+    let dice = rules.dice as i32;
+    let miss = (rules.sides - 1) as f64 / rules.sides as f64;
+    let hit = 1.0 - miss.powi(rolls_per_turn as i32);
+    let binomial: Vec<f64> = (0..=dice)
+        .map(|k| {
+            let ways = (1..=k).fold(1.0, |acc, i| acc * (dice - k + i) as f64 / i as f64);
+            ways * hit.powi(k) * (1.0 - hit).powi(dice - k)
+        })
+        .collect();
+
+    // chances[n] is how likely n more points are, with everything past what's needed counted as needed
+    let mut chances = vec![0.0; needed + 1];
+    chances[0] = 1.0;
+    for &face in open_faces {
+        let mut next = vec![0.0; needed + 1];
+        for (points, chance) in chances.iter().enumerate() {
+            for (k, p) in binomial.iter().enumerate() {
+                next[(points + k * face as usize).min(needed)] += chance * p;
+            }
+        }
+        chances = next;
+    }
+
+    chances[needed]
+}
+
+// the exact chance that rolling the dice that aren't held makes the category, going through every way
+// those dice can land, so four held dice of a little straight missing its 5 come out at 1/6
+pub fn completion_probability(roll: &Roll, target: &ScoreType, rules: &Rules) -> f64 {
+    let free: Vec<usize> = (0..roll.dice.len()).filter(|&i| !roll.holds[i]).collect();
+    let sides = roll.sides as usize;
+    let outcomes = sides.pow(free.len() as u32);
+
+    let mut next = roll.clone();
+    let hits = (0..outcomes)
+        .filter(|&outcome| {
+            let mut rest = outcome;
+            for &i in &free {
+                next.dice[i] = (rest % sides) as u8 + 1;
+                rest /= sides;
+            }
+            qualifies(&next, target, rules)
+        })
+        .count();
+
+    hits as f64 / outcomes as f64
+}
+
+// every score the category can produce, found by scoring every possible roll
+fn achievable_scores(score_type: &ScoreType, rules: &Rules) -> Vec<u8> {
+    let mut scores = Vec::new();
+
+    let sides = rules.sides as u32;
+    for n in 0..sides.pow(rules.dice as u32) {
+        let mut rest = n;
+        let dice = (0..rules.dice)
+            .map(|_| {
+                let die = (rest % sides) as u8 + 1;
+                rest /= sides;
+                die
+            })
+            .collect();
+
+        // every order of the dice is enumerated, so strict order makes no difference here
+        let score = evaluate_score(&Roll::from_dice(dice, rules.sides), score_type, &Rules { strict_order: false, ..*rules });
+        if !scores.contains(&score) {
+            scores.push(score);
+        }
+    }
+
+    scores.sort();
+    scores
+}
+
+// tallies a hand written scorecard given as whitespace separated values in table order,
+// checking each value could actually have been scored in its category
+pub fn scorecard_report(input: &str, rules: &Rules) -> Vec<String> {
+    let categories = rules.categories();
+    let values: Vec<&str> = input.split_whitespace().collect();
+
+    if values.len() != categories.len() {
+        return vec![format!("Expected {} scores but found {}", categories.len(), values.len())];
+    }
+
+    let mut lines = Vec::new();
+    let mut table = ScoreTable::new();
+    let mut valid = true;
+
+    for (score_type, value) in categories.iter().zip(values) {
+        let Ok(score) = value.parse::<u8>() else {
+            lines.push(format!("{:<16}{:>4}  not a score", score_type.name(), value));
+            valid = false;
+            continue;
+        };
+
+        if achievable_scores(score_type, rules).contains(&score) {
+            lines.push(format!("{:<16}{:>4}  ok", score_type.name(), score));
+        } else {
+            lines.push(format!("{:<16}{:>4}  impossible for this category", score_type.name(), score));
+            valid = false;
+        }
+        table.table.insert(*score_type, score);
+    }
+
+    let upper = table.upper_total();
+    let bonus = table.upper_bonus();
+    let total = table.table_total();
+
+    lines.push(String::new());
+    lines.push(format!("Upper section   {:>4}", upper));
+    lines.push(format!("Upper bonus     {:>4}", bonus));
+    lines.push(format!("Lower section   {:>4}", total - upper - bonus));
+    lines.push(format!("TOTAL           {:>4}", total));
+    lines.push(if valid { "Scorecard is valid".to_string() } else { "Scorecard has impossible values".to_string() });
+
+    lines
+}
+
+// canonical rolls with what each should score, checked at runtime by the debug 'verify' command. The upper
+// section scores the same in every ruleset
+const UPPER_VERIFY_CASES: &[([u8; 5], ScoreType, u8)] = &[
+    ([1, 1, 1, 2, 3], ScoreType::Aces, 3),
+    ([2, 2, 5, 5, 6], ScoreType::Twos, 4),
+    ([1, 3, 3, 3, 3], ScoreType::Threes, 12),
+    ([4, 4, 4, 5, 6], ScoreType::Fours, 12),
+    ([4, 1, 4, 6, 4], ScoreType::Fours, 12),
+    ([1, 2, 3, 5, 5], ScoreType::Fives, 10),
+    ([6, 6, 6, 6, 6], ScoreType::Sixes, 30),
+];
+
+const YACHT_VERIFY_CASES: &[([u8; 5], ScoreType, u8)] = &[
+    ([1, 3, 3, 3, 3], ScoreType::FourOfKind, 12),
+    ([1, 1, 2, 3, 4], ScoreType::FourOfKind, 0),
+    ([3, 3, 3, 3, 5], ScoreType::FourOfKind, 12),
+    ([5, 3, 3, 3, 3], ScoreType::FourOfKind, 12),
+    ([6, 6, 6, 6, 1], ScoreType::FourOfKind, 24),
+    ([6, 6, 1, 6, 6], ScoreType::FourOfKind, 24),
+    ([4, 4, 4, 4, 4], ScoreType::FourOfKind, 16),
+    ([2, 2, 3, 3, 3], ScoreType::FullHouse, 25),
+    ([2, 2, 3, 3, 4], ScoreType::FullHouse, 0),
+    ([2, 5, 2, 5, 2], ScoreType::FullHouse, 25),
+    ([4, 4, 4, 4, 4], ScoreType::FullHouse, 25),
+    ([4, 4, 4, 4, 1], ScoreType::FullHouse, 0),
+    ([1, 2, 3, 4, 5], ScoreType::LittleStraight, 30),
+    ([5, 4, 3, 2, 1], ScoreType::LittleStraight, 30),
+    ([3, 1, 4, 2, 5], ScoreType::LittleStraight, 30),
+    ([1, 2, 3, 4, 6], ScoreType::LittleStraight, 0),
+    ([2, 3, 4, 5, 6], ScoreType::BigStraight, 30),
+    ([6, 2, 5, 3, 4], ScoreType::BigStraight, 30),
+    ([1, 2, 3, 4, 5], ScoreType::BigStraight, 0),
+    ([6, 6, 6, 6, 6], ScoreType::Yacht, 50),
+    ([5, 6, 6, 6, 6], ScoreType::Yacht, 0),
+    ([1, 3, 4, 5, 6], ScoreType::Chance, 19),
+    ([2, 5, 2, 6, 2], ScoreType::ThreeOfKind, 17),
+    ([6, 6, 6, 6, 6], ScoreType::ThreeOfKind, 30),
+    ([2, 2, 3, 3, 4], ScoreType::ThreeOfKind, 0),
+];
+
+const GENERALA_VERIFY_CASES: &[([u8; 5], ScoreType, u8)] = &[
+    ([1, 3, 3, 3, 3], ScoreType::FourOfKind, 40),
+    ([1, 1, 2, 3, 4], ScoreType::FourOfKind, 0),
+    ([5, 3, 3, 3, 3], ScoreType::FourOfKind, 40),
+    ([6, 6, 1, 6, 6], ScoreType::FourOfKind, 40),
+    ([4, 4, 4, 4, 4], ScoreType::FourOfKind, 40),
+    ([2, 2, 3, 3, 3], ScoreType::FullHouse, 30),
+    ([2, 2, 3, 3, 4], ScoreType::FullHouse, 0),
+    ([2, 5, 2, 5, 2], ScoreType::FullHouse, 30),
+    ([4, 4, 4, 4, 4], ScoreType::FullHouse, 30),
+    ([1, 2, 3, 4, 5], ScoreType::Straight, 20),
+    ([5, 4, 3, 2, 1], ScoreType::Straight, 20),
+    ([3, 1, 4, 2, 5], ScoreType::Straight, 20),
+    ([2, 3, 4, 5, 6], ScoreType::Straight, 20),
+    ([6, 2, 5, 3, 4], ScoreType::Straight, 20),
+    ([1, 2, 3, 4, 6], ScoreType::Straight, 0),
+    ([6, 6, 6, 6, 6], ScoreType::Yacht, 50),
+    ([5, 6, 6, 6, 6], ScoreType::Yacht, 0),
+];
+
+// cases above that score nothing once the dice have to lie in order
+const STRICT_ORDER_MISSES: &[([u8; 5], ScoreType)] = &[
+    ([6, 6, 1, 6, 6], ScoreType::FourOfKind),
+    ([5, 4, 3, 2, 1], ScoreType::LittleStraight),
+    ([3, 1, 4, 2, 5], ScoreType::LittleStraight),
+    ([6, 2, 5, 3, 4], ScoreType::BigStraight),
+    ([5, 4, 3, 2, 1], ScoreType::Straight),
+    ([3, 1, 4, 2, 5], ScoreType::Straight),
+    ([6, 2, 5, 3, 4], ScoreType::Straight),
+];
+
+// the canonical cases that apply under the rules, with what they should score there. Categories that aren't
+// played and hands with faces the dice don't have are left out
+pub fn verify_cases(rules: &Rules) -> Vec<([u8; 5], ScoreType, u8)> {
+    let lower = match rules.ruleset {
+        Ruleset::Yacht => YACHT_VERIFY_CASES,
+        Ruleset::Generala => GENERALA_VERIFY_CASES,
+    };
+    let categories = rules.categories();
+
+    UPPER_VERIFY_CASES
+        .iter()
+        .chain(lower)
+        .filter(|(dice, score_type, _)| categories.contains(score_type) && dice.iter().all(|&die| die <= rules.sides))
+        .map(|&(dice, score_type, expected)| {
+            let expected = if rules.strict_order && STRICT_ORDER_MISSES.contains(&(dice, score_type)) { 0 } else { expected };
+            let expected = match (score_type, rules.chance_cap) {
+                (ScoreType::Chance, Some(cap)) => expected.min(cap),
+                _ => expected,
+            };
+            (dice, score_type, expected)
+        })
+        .collect()
+}
+
+// runs every canonical case for the rules through evaluate_score, returning a line per mismatch
+pub fn verify_scoring(rules: &Rules) -> Vec<String> {
+    verify_cases(rules)
+        .iter()
+        .filter_map(|(dice, score_type, expected)| {
+            let roll = Roll::from_dice(dice.to_vec(), rules.sides);
+            let actual = evaluate_score(&roll, score_type, rules);
+            if actual == *expected {
+                None
+            } else {
+                Some(format!("{:?} as {}: expected {}, got {}", dice, score_type.name(), expected, actual))
+            }
+        })
+        .collect()
+}
+
+// whether the dice score anything at all in the category
+pub fn qualifies(roll: &Roll, score_type: &ScoreType, rules: &Rules) -> bool {
+    evaluate_score(roll, score_type, rules) > 0
+}
+
+// the upper section score for face n: how many dice show it times the face, so three 4s make 12.
+// Anything that isn't a die face scores 0
+fn sum_of_face(roll: &Roll, n: u8) -> u8 {
+    if !(1..=6).contains(&n) {
+        return 0;
+    }
+    roll.count_face(n) * n
+}
+
+// whether the faces in run all show, next to each other and in order when strict order is on
+fn has_run(roll: &Roll, run: std::ops::RangeInclusive<u8>, rules: &Rules) -> bool {
+    if rules.strict_order {
+        let run: Vec<u8> = run.collect();
+        roll.dice.windows(run.len()).any(|window| window == run)
+    } else {
+        run.into_iter().all(|face| roll.dice.contains(&face))
+    }
+}
+
+// scores a full hand of rolled dice. No dice at all, or a die outside 1 to the number of sides, means the hand
+// isn't really there yet (never rolled, or only partly filled in), and any such hand scores 0 in every category
+// rather than panicking or scoring something like a yacht of blanks
+pub fn evaluate_score(roll: &Roll, score_type: &ScoreType, rules: &Rules) -> u8 {
+    if roll.dice.is_empty() || roll.dice.iter().any(|die| !(1..=roll.sides).contains(die)) {
+        return 0;
+    }
+
+    // the patterns below expect the dice low to high, strict order scores the dice exactly as they lie
+    let mut sorted = roll.clone();
+    if !rules.strict_order {
+        sorted.sort();
+    }
+    let roll = &sorted;
+
+    let result = match score_type {
+        ScoreType::Aces => sum_of_face(roll, 1),
+        ScoreType::Twos => sum_of_face(roll, 2),
+        ScoreType::Threes => sum_of_face(roll, 3),
+        ScoreType::Fours => sum_of_face(roll, 4),
+        ScoreType::Fives => sum_of_face(roll, 5),
+        ScoreType::Sixes => sum_of_face(roll, 6),
+
+        // Yacht rules: four matching dice score the sum of those four, the fifth die doesn't count. Strict order
+        // also wants the four side by side. Generala pays a flat 40 for them instead
+        ScoreType::FourOfKind => {
+            let counts = roll.face_counts();
+            let Some(face) = (1..=roll.sides).find(|&face| counts[face as usize] >= 4) else {
+                return 0;
+            };
+            let side_by_side = roll.dice.windows(4).any(|window| window.iter().all(|&x| x == face));
+
+            if rules.strict_order && !side_by_side {
+                0
+            } else if rules.ruleset == Ruleset::Generala {
+                score_type.max_score(rules)
+            } else {
+                face * 4
+            }
+        }
+        // any three of one face and two of another, and a yacht counts as a full house too
+        ScoreType::FullHouse => {
+            let mut counts: Vec<u8> = roll.face_counts().into_iter().filter(|&count| count > 0).collect();
+            counts.sort();
+
+            match counts[..] {
+                [2, 3] | [5] => score_type.max_score(rules),
+                _ => 0,
+            }
+        },
+        // any extra dice beyond the run of five don't matter
+        ScoreType::LittleStraight => if has_run(roll, 1..=5, rules) { 30 } else { 0 },
+        ScoreType::BigStraight => if has_run(roll, 2..=6, rules) { 30 } else { 0 },
+        // Generala's only straight, either run of five
+        ScoreType::Straight => if has_run(roll, 1..=5, rules) || has_run(roll, 2..=6, rules) { score_type.max_score(rules) } else { 0 },
+        ScoreType::Yacht => {
+            let i = roll.dice[0];
+            if roll.dice.iter().all(|&x| x == i) {
+                50
+            } else {
+                0
+            }
+        }
+        ScoreType::ThreeOfKind => {
+            if roll.face_counts().iter().any(|&count| count >= 3) {
+                roll.dice.iter().sum()
+            } else {
+                0
+            }
+        }
+        ScoreType::Chance => {
+            let sum: u8 = roll.dice.iter().sum();
+            sum.min(score_type.max_score(rules))
+        }
+    };
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_passes_for_default_rules() {
+        assert_eq!(verify_scoring(&Rules::default()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn verify_passes_for_every_profile() {
+        for ruleset in [Ruleset::Yacht, Ruleset::Generala] {
+            for strict_order in [false, true] {
+                for rules in [
+                    Rules { ruleset, strict_order, ..Rules::default() },
+                    Rules { ruleset, strict_order, chance_cap: Some(15), ..Rules::default() },
+                    Rules { ruleset, strict_order, dice: 6, sides: 8, ..Rules::default() },
+                    Rules { ruleset, strict_order, sides: 4, ..Rules::default() },
+                ] {
+                    assert!(!verify_cases(&rules).is_empty());
+                    assert_eq!(verify_scoring(&rules), Vec::<String>::new(), "{:?}", rules);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn verify_checks_the_active_ruleset() {
+        let generala = Rules { ruleset: Ruleset::Generala, ..Rules::default() };
+        assert!(verify_cases(&generala).iter().any(|&(_, score_type, expected)| score_type == ScoreType::FourOfKind && expected == 40));
+        assert!(verify_cases(&generala).iter().all(|(_, score_type, _)| *score_type != ScoreType::Chance));
+    }
+
+    #[test]
+    fn no_dice_score_nothing() {
+        for dice in [5, 6] {
+            for ruleset in [Ruleset::Yacht, Ruleset::Generala] {
+                let rules = Rules { dice, ruleset, ..Rules::default() };
+                let roll = Roll::from_dice(vec![], rules.sides);
+                for score_type in ScoreType::all() {
+                    assert_eq!(evaluate_score(&roll, &score_type, &rules), 0, "{} with {} dice", score_type.name(), dice);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unrolled_dice_score_nothing() {
+        let rules = Rules::default();
+        let roll = Roll::from_dice(vec![0, 0, 3, 3, 3], rules.sides);
+        for score_type in ScoreType::all() {
+            assert_eq!(evaluate_score(&roll, &score_type, &rules), 0, "{}", score_type.name());
+        }
+    }
+
+    fn score(dice: &[u8], score_type: ScoreType) -> u8 {
+        evaluate_score(&Roll::try_from_dice(dice.to_vec(), 6).unwrap(), &score_type, &Rules::default())
+    }
+
+    #[test]
+    fn all_scores_for_a_known_roll() {
+        let rules = Rules::default();
+        let mut table = ScoreTable::new();
+        table.table.insert(ScoreType::Chance, 12);
+        table.turn_filled.insert(ScoreType::Chance, 1);
+        let scores = table.all_scores(&Roll::try_from_dice(vec![2, 3, 4, 5, 6], 6).unwrap(), &rules);
+
+        let expected = [
+            (ScoreType::Aces, 0),
+            (ScoreType::Twos, 2),
+            (ScoreType::Threes, 3),
+            (ScoreType::Fours, 4),
+            (ScoreType::Fives, 5),
+            (ScoreType::Sixes, 6),
+            (ScoreType::FourOfKind, 0),
+            (ScoreType::FullHouse, 0),
+            (ScoreType::LittleStraight, 0),
+            (ScoreType::BigStraight, 30),
+            (ScoreType::Yacht, 0),
+            (ScoreType::Chance, 20),
+            (ScoreType::ThreeOfKind, 0),
+        ];
+        assert_eq!(scores.len(), expected.len());
+        for ((score_type, score, _), (expected_type, expected_score)) in scores.iter().zip(expected) {
+            assert_eq!((*score_type, *score), (expected_type, expected_score));
+        }
+        assert_eq!(scores[11].2, Some(1));
+        assert_eq!(scores[0].2, None);
+    }
+
+    // from_u8 became from_word when the table started varying by ruleset, a number is still a place on the table
+    #[test]
+    fn out_of_range_numbers_are_no_category() {
+        let rules = Rules::default();
+        for word in ["99", "0", "14", "-1", "255", "256"] {
+            assert_eq!(ScoreType::from_word(word, &rules), None, "{}", word);
+        }
+        assert_eq!(ScoreType::from_word("13", &rules), Some(ScoreType::ThreeOfKind));
+        let generala = Rules { ruleset: Ruleset::Generala, ..Rules::default() };
+        assert_eq!(ScoreType::from_word("10", &generala), Some(ScoreType::Yacht));
+        assert_eq!(ScoreType::from_word("11", &generala), None);
+    }
+
+    #[test]
+    fn hand_entered_scorecard_is_validated() {
+        let rules = Rules::default();
+        // 63 in the upper section for the bonus, then 24 + 25 + 30 + 30 + 50 + 22 + 18 below
+        let report = scorecard_report("3 6 9 12 15 18 24 25 30 30 50 22 18", &rules);
+        assert!(report.iter().take(13).all(|line| line.ends_with("ok")), "{:?}", report);
+        assert!(report.contains(&"Upper section     63".to_string()));
+        assert!(report.contains(&"Upper bonus       35".to_string()));
+        assert!(report.contains(&"TOTAL            297".to_string()));
+        assert_eq!(report.last().unwrap(), "Scorecard is valid");
+
+        // 7 can't be made from aces, and a full house is always 25 or nothing
+        let report = scorecard_report("7 6 9 12 15 18 24 20 30 30 50 22 18", &rules);
+        assert!(report[0].ends_with("impossible for this category"));
+        assert!(report[7].ends_with("impossible for this category"));
+        assert_eq!(report.last().unwrap(), "Scorecard has impossible values");
+
+        assert_eq!(scorecard_report("1 2 3", &rules), vec!["Expected 13 scores but found 3"]);
+        assert!(scorecard_report("x 6 9 12 15 18 24 25 30 30 50 22 18", &rules)[0].ends_with("not a score"));
+    }
+
+    #[test]
+    fn best_open_score_moves_as_categories_fill() {
+        let rules = Rules::default();
+        let roll = Roll::try_from_dice(vec![5, 5, 5, 6, 6], 6).unwrap();
+        let mut table = ScoreTable::new();
+
+        // Chance and Three Of A Kind tie, the later one on the table wins
+        for (best, score) in [(ScoreType::ThreeOfKind, 27), (ScoreType::Chance, 27), (ScoreType::FullHouse, 25), (ScoreType::Fives, 15), (ScoreType::Sixes, 12)] {
+            assert_eq!(table.best_open_score(&roll, &rules), Some((best, score)));
+            table.table.insert(best, 0);
+        }
+
+        for score_type in ScoreType::all() {
+            table.table.insert(score_type, 0);
+        }
+        assert_eq!(table.best_open_score(&roll, &rules), None);
+    }
+
+    #[test]
+    fn bonus_odds_on_and_off_track() {
+        let rules = Rules::default();
+        // one six in three rolls of five dice makes the 3 points still needed
+        let on_track = estimate_bonus_odds(60, &[6], 3, &rules);
+        assert!(on_track > 0.9, "{}", on_track);
+        // five of every face left wouldn't make the 43 needed
+        assert_eq!(estimate_bonus_odds(20, &[1, 2, 3], 3, &rules), 0.0);
+        // a long way off with most of the section open is possible but unlikely
+        let off_track = estimate_bonus_odds(0, &[1, 2, 3, 4, 5], 3, &rules);
+        assert!(off_track > 0.0 && off_track < 0.1, "{}", off_track);
+
+        assert_eq!(estimate_bonus_odds(UPPER_BONUS_THRESHOLD, &[], 3, &rules), 1.0);
+    }
+
+    #[test]
+    fn unsorted_straight_in_both_modes() {
+        let strict = Rules { strict_order: true, ..Rules::default() };
+        let unsorted = Roll::try_from_dice(vec![3, 1, 4, 2, 5], 6).unwrap();
+        let sorted = Roll::try_from_dice(vec![1, 2, 3, 4, 5], 6).unwrap();
+
+        assert_eq!(evaluate_score(&unsorted, &ScoreType::LittleStraight, &Rules::default()), 30);
+        assert_eq!(evaluate_score(&unsorted, &ScoreType::LittleStraight, &strict), 0);
+        assert_eq!(evaluate_score(&sorted, &ScoreType::LittleStraight, &strict), 30);
+
+        // four of a kind needs its four side by side in strict order
+        let split = Roll::try_from_dice(vec![6, 6, 1, 6, 6], 6).unwrap();
+        assert_eq!(evaluate_score(&split, &ScoreType::FourOfKind, &Rules::default()), 24);
+        assert_eq!(evaluate_score(&split, &ScoreType::FourOfKind, &strict), 0);
+    }
+
+    #[test]
+    fn chance_is_clamped_to_its_cap() {
+        let capped = Rules { chance_cap: Some(20), ..Rules::default() };
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![6, 6, 6, 6, 5], 6).unwrap(), &ScoreType::Chance, &capped), 20);
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![1, 2, 3, 4, 5], 6).unwrap(), &ScoreType::Chance, &capped), 15);
+        assert_eq!(ScoreType::Chance.max_score(&capped), 20);
+
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![6, 6, 6, 6, 5], 6).unwrap(), &ScoreType::Chance, &Rules::default()), 29);
+        assert_eq!(ScoreType::Chance.max_score(&Rules::default()), 30);
+    }
+
+    #[test]
+    fn straights_score_in_any_order() {
+        assert_eq!(score(&[3, 1, 4, 2, 5], ScoreType::LittleStraight), 30);
+        assert_eq!(score(&[5, 4, 3, 2, 1], ScoreType::LittleStraight), 30);
+        assert_eq!(score(&[6, 2, 5, 3, 4], ScoreType::BigStraight), 30);
+        assert_eq!(score(&[6, 2, 5, 3, 4], ScoreType::LittleStraight), 0);
+        assert_eq!(score(&[3, 1, 4, 2, 2], ScoreType::LittleStraight), 0);
+    }
+
+    #[test]
+    fn four_of_a_kind_counts_the_four() {
+        assert_eq!(score(&[3, 3, 3, 3, 5], ScoreType::FourOfKind), 12);
+        assert_eq!(score(&[5, 3, 3, 3, 3], ScoreType::FourOfKind), 12);
+        assert_eq!(score(&[4, 4, 4, 4, 4], ScoreType::FourOfKind), 16);
+        assert_eq!(score(&[4, 4, 4, 2, 2], ScoreType::FourOfKind), 0);
+
+        let generala = Rules { ruleset: Ruleset::Generala, ..Rules::default() };
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![5, 3, 3, 3, 3], 6).unwrap(), &ScoreType::FourOfKind, &generala), 40);
+    }
+
+    #[test]
+    fn upper_bonus_starts_at_63() {
+        let mut table = ScoreTable::new();
+        for (score_type, score) in [(ScoreType::Aces, 3), (ScoreType::Twos, 6), (ScoreType::Threes, 9), (ScoreType::Fours, 12), (ScoreType::Fives, 15), (ScoreType::Sixes, 18)] {
+            table.table.insert(score_type, score);
+        }
+        table.table.insert(ScoreType::Chance, 20);
+        assert_eq!(table.upper_total(), 63);
+        assert_eq!(table.upper_bonus(), 35);
+        assert_eq!(table.table_total(), 63 + 35 + 20);
+
+        table.table.insert(ScoreType::Aces, 2);
+        assert_eq!(table.upper_total(), 62);
+        assert_eq!(table.upper_bonus(), 0);
+        assert_eq!(table.table_total(), 62 + 20);
+    }
+
+    #[test]
+    fn full_house_in_any_order() {
+        assert_eq!(score(&[2, 5, 2, 5, 2], ScoreType::FullHouse), 25);
+        assert_eq!(score(&[5, 2, 5, 2, 2], ScoreType::FullHouse), 25);
+        // a yacht counts as a full house too
+        assert_eq!(score(&[3, 3, 3, 3, 3], ScoreType::FullHouse), 25);
+        assert_eq!(score(&[2, 2, 2, 2, 5], ScoreType::FullHouse), 0);
+        assert_eq!(score(&[2, 2, 5, 5, 6], ScoreType::FullHouse), 0);
+    }
+
+    #[test]
+    fn three_of_a_kind_sums_every_die() {
+        assert_eq!(score(&[2, 5, 2, 6, 2], ScoreType::ThreeOfKind), 17);
+        assert_eq!(score(&[4, 4, 1, 4, 4], ScoreType::ThreeOfKind), 17);
+        assert_eq!(score(&[2, 2, 3, 3, 5], ScoreType::ThreeOfKind), 0);
+        assert_eq!(ScoreType::from_word("threeofakind", &Rules::default()), Some(ScoreType::ThreeOfKind));
+        assert_eq!(ScoreType::from_word("13", &Rules::default()), Some(ScoreType::ThreeOfKind));
+    }
+
+    #[test]
+    fn sum_of_face_counts_times_face() {
+        let roll = Roll::try_from_dice(vec![4, 1, 4, 6, 4], 6).unwrap();
+        assert_eq!(sum_of_face(&roll, 4), 12);
+        assert_eq!(sum_of_face(&roll, 1), 1);
+        assert_eq!(sum_of_face(&roll, 2), 0);
+        assert_eq!(sum_of_face(&roll, 0), 0);
+        assert_eq!(sum_of_face(&roll, 7), 0);
+    }
+
+    #[test]
+    fn open_categories_leave_out_the_scored() {
+        let rules = Rules::default();
+        let mut table = ScoreTable::new();
+        table.score_on_table(&ScoreType::Aces, &Roll::try_from_dice(vec![1, 1, 2, 3, 4], 6).unwrap(), &rules);
+        table.score_on_table(&ScoreType::Yacht, &Roll::try_from_dice(vec![5, 5, 5, 5, 5], 6).unwrap(), &rules);
+
+        let expected: Vec<ScoreType> = rules.categories().into_iter().filter(|&score_type| score_type != ScoreType::Aces && score_type != ScoreType::Yacht).collect();
+        assert_eq!(table.open_categories(&rules), expected);
+        assert_eq!(expected.len(), 11);
+    }
+
+    #[test]
+    fn best_open_score_tie_goes_to_the_higher_value_category() {
+        let rules = Rules::default();
+        let mut table = ScoreTable::new();
+
+        // four sixes are 24 in Sixes and in Four Of A Kind, and Sixes can be worth 30 to Four Of A Kind's 24
+        let roll = Roll::try_from_dice(vec![6, 6, 2, 6, 6], 6).unwrap();
+        table.scratch_on_table(&ScoreType::Chance);
+        table.scratch_on_table(&ScoreType::ThreeOfKind);
+        assert_eq!(table.best_open_score(&roll, &rules), Some((ScoreType::Sixes, 24)));
+        table.scratch_on_table(&ScoreType::Sixes);
+        assert_eq!(table.best_open_score(&roll, &rules), Some((ScoreType::FourOfKind, 24)));
+
+        // Three Of A Kind and Chance both sum the dice and are worth 30 at most, Three Of A Kind comes later in the table
+        let mut table = ScoreTable::new();
+        let roll = Roll::try_from_dice(vec![6, 6, 6, 5, 4], 6).unwrap();
+        assert_eq!(table.best_open_score(&roll, &rules), Some((ScoreType::ThreeOfKind, 27)));
+        table.scratch_on_table(&ScoreType::ThreeOfKind);
+        assert_eq!(table.best_open_score(&roll, &rules), Some((ScoreType::Chance, 27)));
+
+        // only five sixes make Sixes tie with Chance, and once Yacht is filled that's a joker for the Sixes box
+        let roll = Roll::try_from_dice(vec![6, 6, 6, 6, 6], 6).unwrap();
+        for score_type in [ScoreType::Yacht, ScoreType::FourOfKind, ScoreType::FullHouse] {
+            table.scratch_on_table(&score_type);
+        }
+        assert_eq!(table.best_open_score(&roll, &rules), Some((ScoreType::Sixes, 30)));
+        // then it's any open lower box, where the straights score 30 as well and Chance comes after them
+        table.scratch_on_table(&ScoreType::Sixes);
+        assert_eq!(table.best_open_score(&roll, &rules), Some((ScoreType::Chance, 30)));
+    }
+
+    #[test]
+    fn each_ruleset_scores_its_own_way() {
+        let yacht = Rules::default();
+        let generala = Rules { ruleset: Ruleset::Generala, ..Rules::default() };
+
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![2, 2, 3, 3, 3], 6).unwrap(), &ScoreType::FullHouse, &yacht), 25);
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![2, 2, 3, 3, 3], 6).unwrap(), &ScoreType::FullHouse, &generala), 30);
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![3, 2, 4, 1, 5], 6).unwrap(), &ScoreType::Straight, &generala), 20);
+        assert_eq!(evaluate_score(&Roll::try_from_dice(vec![6, 6, 6, 6, 6], 6).unwrap(), &ScoreType::Yacht, &generala), 50);
+
+        assert!(yacht.categories().contains(&ScoreType::Chance));
+        assert!(!generala.categories().contains(&ScoreType::Chance));
+        assert!(generala.categories().contains(&ScoreType::Straight));
+        assert_eq!(yacht.categories().len(), 13);
+        assert_eq!(generala.categories().len(), 10);
+    }
+
+    #[test]
+    fn aliases_and_clear_prefixes_parse() {
+        for (alias, score_type) in SCORE_ALIASES {
+            assert_eq!(parse_score_type(alias), Some(score_type), "{}", alias);
+        }
+        for (word, score_type) in [("full", ScoreType::FullHouse), ("little", ScoreType::LittleStraight), ("big", ScoreType::BigStraight), ("y", ScoreType::Yacht), ("ch", ScoreType::Chance), ("FH", ScoreType::FullHouse)] {
+            assert_eq!(parse_score_type(word), Some(score_type), "{}", word);
+        }
+
+        // fours or fourofakind, and threes or threeofakind
+        assert_eq!(parse_score_type("four"), None);
+        assert_eq!(parse_score_type("t"), None);
+        assert_eq!(parse_score_type(""), None);
+    }
+
+    #[test]
+    fn scores_each_section() {
+        assert_eq!(score(&[1, 1, 1, 2, 3], ScoreType::Aces), 3);
+        assert_eq!(score(&[6, 6, 2, 6, 1], ScoreType::Sixes), 18);
+        assert_eq!(score(&[2, 2, 3, 3, 3], ScoreType::FullHouse), 25);
+        assert_eq!(score(&[1, 3, 4, 5, 6], ScoreType::Chance), 19);
+        assert_eq!(score(&[5, 5, 5, 5, 5], ScoreType::Yacht), 50);
+        assert_eq!(score(&[5, 5, 5, 5, 4], ScoreType::Yacht), 0);
+    }
+
+    #[test]
+    fn categories_fill_once() {
+        let rules = Rules::default();
+        let mut table = ScoreTable::new();
+        let roll = Roll::try_from_dice(vec![4, 4, 4, 1, 2], 6).unwrap();
+
+        assert!(table.score_on_table(&ScoreType::Fours, &roll, &rules));
+        assert!(!table.score_on_table(&ScoreType::Fours, &roll, &rules));
+        assert!(table.score_on_table(&ScoreType::Chance, &roll, &rules));
+        assert_eq!(table.table.get(&ScoreType::Fours), Some(&12));
+        assert_eq!(table.table_total(), 27);
+        assert_eq!(table.open_categories(&rules).len(), 11);
+    }
+
+    #[test]
+    fn categories_parse_by_number_or_name() {
+        let rules = Rules::default();
+        assert_eq!(ScoreType::from_word("1", &rules), Some(ScoreType::Aces));
+        assert_eq!(ScoreType::from_word("13", &rules), Some(ScoreType::ThreeOfKind));
+        assert_eq!(ScoreType::from_word("14", &rules), None);
+        assert_eq!(ScoreType::from_word("yacht", &rules), Some(ScoreType::Yacht));
+        assert_eq!(ScoreType::from_word("straight", &rules), None);
+    }
+
+    #[test]
+    fn table_rows_past_the_last_are_none() {
+        let rules = Rules::default();
+        assert_eq!(ScoreType::from_index(0, &rules), Some(ScoreType::Aces));
+        assert_eq!(ScoreType::from_index(12, &rules), Some(ScoreType::ThreeOfKind));
+        assert_eq!(ScoreType::from_index(13, &rules), None);
+        assert_eq!(ScoreType::from_index(99, &rules), None);
+
+        // generala plays fewer categories, so its table ends sooner
+        let generala = Rules { ruleset: Ruleset::Generala, ..Rules::default() };
+        let rows = generala.categories().len() as u8;
+        assert_eq!(ScoreType::from_index(rows - 1, &generala), generala.categories().last().copied());
+        assert_eq!(ScoreType::from_index(rows, &generala), None);
+    }
+
+    #[test]
+    fn completion_odds_for_known_holds() {
+        let rules = Rules::default();
+        let odds = |dice: Vec<u8>, holds: Vec<bool>, target| {
+            let mut roll = Roll::try_from_dice(dice, 6).unwrap();
+            roll.holds = holds;
+            completion_probability(&roll, &target, &rules)
+        };
+        let close = |left: f64, right: f64| (left - right).abs() < 1e-9;
+
+        // one die away, with the four right dice held
+        assert!(close(odds(vec![1, 2, 3, 4, 6], vec![true, true, true, true, false], ScoreType::LittleStraight), 1.0 / 6.0));
+        assert!(close(odds(vec![6, 6, 6, 6, 2], vec![true, true, true, true, false], ScoreType::Yacht), 1.0 / 6.0));
+        // all five free, any of the six faces five times over
+        assert!(close(odds(vec![1, 2, 3, 4, 6], vec![false; 5], ScoreType::Yacht), 6.0 / 7776.0));
+        // nothing left to roll, so it's already made or it never will be
+        assert!(close(odds(vec![2, 2, 3, 3, 3], vec![true; 5], ScoreType::FullHouse), 1.0));
+        assert!(close(odds(vec![1, 1, 1, 1, 2], vec![true; 5], ScoreType::Yacht), 0.0));
+    }
+
+    #[test]
+    fn every_active_category_has_a_rule() {
+        for rules in [Rules::default(), Rules { ruleset: Ruleset::Generala, ..Rules::default() }, Rules { dice: 6, sides: 8, ..Rules::default() }] {
+            let panel = scoring_panel(&rules);
+            for score_type in rules.categories() {
+                let rule = score_type.rule(&rules);
+                assert!(rule.ends_with(&score_type.max_score(&rules).to_string()), "{}: {}", score_type.name(), rule);
+                // the upper categories share one line, named by the first and last of them
+                let listed = panel.iter().any(|line| line.contains(score_type.name()));
+                assert!(listed || score_type.face().is_some(), "{:?} {:?}", score_type, rules);
+            }
+        }
+    }
+
+    #[test]
+    fn section_totals_for_a_full_card() {
+        let mut table = ScoreTable::new();
+        let card = [
+            (ScoreType::Aces, 3), (ScoreType::Twos, 6), (ScoreType::Threes, 9), (ScoreType::Fours, 12), (ScoreType::Fives, 15), (ScoreType::Sixes, 18),
+            (ScoreType::ThreeOfKind, 20), (ScoreType::FourOfKind, 16), (ScoreType::FullHouse, 25), (ScoreType::LittleStraight, 30),
+            (ScoreType::BigStraight, 30), (ScoreType::Chance, 22), (ScoreType::Yacht, 50),
+        ];
+        table.table.extend(card);
+        table.yacht_bonus = 100;
+
+        assert_eq!(table.upper_total(), 63);
+        assert_eq!(table.upper_bonus(), 35);
+        // the yacht bonus isn't part of the lower section
+        assert_eq!(table.lower_total(), 193);
+        assert_eq!(table.table_total(), 63 + 35 + 193 + 100);
+
+        table.table.insert(ScoreType::Aces, 2);
+        assert_eq!((table.upper_total(), table.upper_bonus(), table.lower_total()), (62, 0, 193));
+    }
+
+    #[test]
+    fn scratched_boxes_show_0_and_unplayed_x() {
+        let rules = Rules::default();
+        let mut table = ScoreTable::new();
+        assert!(table.scratch_on_table(&ScoreType::Yacht));
+        // a hand worth nothing in the category is stored as a 0 as well
+        assert!(table.score_on_table(&ScoreType::Sixes, &Roll::try_from_dice(vec![1, 2, 3, 4, 5], 6).unwrap(), &rules));
+
+        assert_eq!(table.get_table_value(&ScoreType::Yacht), " 0");
+        assert_eq!(table.get_table_value(&ScoreType::Sixes), " 0");
+        assert_eq!(table.get_table_value(&ScoreType::Chance), " X ");
+    }
+
+    #[test]
+    fn totals_past_what_a_u8_holds() {
+        // every box at the most a u8 holds, so any sum kept in a u8 would overflow
+        let mut table = ScoreTable::new();
+        table.table.extend(ScoreType::all().map(|score_type| (score_type, u8::MAX)));
+        table.yacht_bonus = 1200;
+        assert_eq!(table.upper_total(), 6 * 255);
+        assert_eq!(table.lower_total(), 8 * 255);
+        assert_eq!(table.table_total(), 14 * 255 + UPPER_BONUS + 1200);
+
+        // the best card the biggest dice allow
+        let rules = Rules { dice: 6, sides: 9, ..Rules::default() };
+        let mut table = ScoreTable::new();
+        table.table.extend(rules.categories().into_iter().map(|score_type| (score_type, score_type.max_score(&rules))));
+        let best: u16 = rules.categories().iter().map(|score_type| score_type.max_score(&rules) as u16).sum();
+        assert_eq!(table.table_total(), best + UPPER_BONUS);
+    }
+}
@@ -0,0 +1,1077 @@
+use std::{collections::HashMap, io::{self, stdout, Write}, thread};
+
+use crossterm::{ExecutableCommand, terminal, QueueableCommand, cursor, style::{self, Stylize, Color, Attribute}};
+use signal_hook::{consts::SIGINT, iterator::Signals};
+
+use crate::{game::{COMMANDS, Game, GameStates, HintLevel, find_command}, persist::{LEADERBOARD_PATH, LEADERBOARD_SIZE, load_leaderboard, saved_slots, top_scores}, score::{Rules, ScoreType, UPPER_BONUS_THRESHOLD}};
+
+pub struct DrawValues {
+    pub score_table_corner: (u16, u16),
+    pub dice_corner: (u16, u16),
+    pub game_status_pos: (u16, u16),
+    pub prompt_pos: (u16, u16),
+    pub title_pos: (u16, u16),
+    pub panel_pos: (u16, u16),
+    pub legend_pos: (u16, u16),
+    pub dice_colors: DiceColors,
+    pub dice_style: DiceStyle,
+    // what each pip is drawn with
+    pub pip: char,
+    // the dice are shown lowest to highest without being moved, each with its number underneath
+    pub sorted_view: bool,
+    pub theme: Theme,
+    // the ruleset's categories, in table order
+    pub categories: Vec<ScoreType>,
+    // a line for each command in the shortcut legend
+    pub legend: Vec<String>,
+}
+
+// the standard board, where everything goes and how it's drawn before any command line options
+impl Default for DrawValues {
+    fn default() -> Self {
+        DrawValues {
+            score_table_corner: (3, 3),
+            dice_corner: (35, 12),
+            game_status_pos: (60, 3),
+            prompt_pos: (3, 31),
+            title_pos: (40, 0),
+            panel_pos: (35, 20),
+            legend_pos: (35, 3),
+            dice_colors: DiceColors { free: Color::White, held: Color::Green, wanted: Color::Yellow },
+            dice_style: DiceStyle::Pips,
+            pip: 'O',
+            sorted_view: false,
+            theme: Theme::from_name("classic").unwrap(),
+            categories: Rules::default().categories(),
+            legend: legend_lines(),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DiceStyle {
+    Pips,
+    Numbers,
+    Unicode,
+}
+
+pub struct DiceColors {
+    pub free: Color,
+    pub held: Color,
+    pub wanted: Color,
+}
+
+impl DiceColors {
+    fn for_hold(&self, held: bool) -> Color {
+        if held { self.held } else { self.free }
+    }
+}
+
+pub fn parse_color(name: &str) -> Option<Color> {
+    match name {
+        "white" => Some(Color::White),
+        "grey" | "gray" => Some(Color::Grey),
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "magenta" => Some(Color::Magenta),
+        "cyan" => Some(Color::Cyan),
+        _ => None,
+    }
+}
+
+// the board's colours other than the dice, a (foreground, background) pair for anything drawn on a block of colour
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Theme {
+    border: Color,
+    title: (Color, Color),
+    first_roll: (Color, Color),
+    middle_roll: (Color, Color),
+    final_roll: (Color, Color),
+    game_over: (Color, Color),
+    current_player: (Color, Color),
+    preview: Color,
+    error: Color,
+}
+
+pub const THEME_NAMES: [&str; 3] = ["classic", "contrast", "mono"];
+
+impl Theme {
+    pub fn from_name(name: &str) -> Result<Theme, String> {
+        match name {
+            "classic" => Ok(Theme {
+                border: Color::White,
+                title: (Color::Black, Color::White),
+                first_roll: (Color::Yellow, Color::Green),
+                middle_roll: (Color::Black, Color::Yellow),
+                final_roll: (Color::White, Color::Red),
+                game_over: (Color::Yellow, Color::Blue),
+                current_player: (Color::Black, Color::Yellow),
+                preview: Color::DarkYellow,
+                error: Color::Red,
+            }),
+            // dark text on the brightest colours
+            "contrast" => Ok(Theme {
+                border: Color::White,
+                title: (Color::Black, Color::White),
+                first_roll: (Color::Black, Color::Green),
+                middle_roll: (Color::Black, Color::Yellow),
+                final_roll: (Color::Black, Color::Red),
+                game_over: (Color::Black, Color::Cyan),
+                current_player: (Color::Black, Color::White),
+                preview: Color::Yellow,
+                error: Color::Red,
+            }),
+            "mono" => Ok(Theme {
+                border: Color::White,
+                title: (Color::Black, Color::White),
+                first_roll: (Color::Black, Color::White),
+                middle_roll: (Color::Black, Color::White),
+                final_roll: (Color::Black, Color::White),
+                game_over: (Color::Black, Color::White),
+                current_player: (Color::Black, Color::White),
+                preview: Color::Grey,
+                error: Color::White,
+            }),
+            _ => Err(format!("Unknown theme '{}', try {}", name, THEME_NAMES.join(", "))),
+        }
+    }
+}
+
+pub const GAME_WIDTH: u16 = 95;
+pub const GAME_HEIGHT: u16 = 35;
+const PANEL_HEIGHT: u16 = 9;
+pub const PANEL_WIDTH: u16 = 56;
+const MSG_LINES: u16 = 2;
+
+pub fn terminal_fits((cols, rows): (u16, u16)) -> bool {
+    cols >= GAME_WIDTH && rows >= GAME_HEIGHT
+}
+
+// which die goes in each place on screen, left to right. Sorted, equal faces keep their order
+fn display_order(dice: &[u8], sorted: bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..dice.len()).collect();
+    if sorted {
+        order.sort_by_key(|&i| dice[i]);
+    }
+    order
+}
+
+pub fn ring_bell(stdout: &mut impl Write) {
+    stdout.queue(style::Print("\x07")).unwrap();
+    stdout.flush().unwrap();
+}
+
+pub fn draw_too_small(stdout: &mut impl Write, (cols, rows): (u16, u16)) {
+    stdout.queue(terminal::Clear(terminal::ClearType::All)).unwrap();
+    stdout.queue(cursor::MoveTo(0, 0)).unwrap();
+    stdout.queue(style::Print(format!("Please enlarge your terminal to {}x{} (it's {}x{} now)", GAME_WIDTH, GAME_HEIGHT, cols, rows))).unwrap();
+    stdout.queue(cursor::MoveTo(0, 1)).unwrap();
+    stdout.queue(style::Print("--> ")).unwrap();
+    stdout.flush().unwrap();
+}
+
+// puts the terminal back how the player had it, whether the game quits, panics or is interrupted
+fn restore_terminal() {
+    let _ = terminal::disable_raw_mode();
+    let _ = reset_screen(&mut stdout());
+}
+
+// default colours on a cleared screen, with the cursor home and showing again
+fn reset_screen(stdout: &mut impl Write) -> io::Result<()> {
+    stdout
+        .queue(style::ResetColor)?
+        .queue(terminal::Clear(terminal::ClearType::All))?
+        .queue(cursor::MoveTo(0, 0))?
+        .queue(cursor::Show)?;
+    stdout.flush()
+}
+
+pub struct TerminalGuard {
+    // what dropping the guard runs, always restore_terminal outside of tests
+    restore: fn(),
+}
+
+impl TerminalGuard {
+    // the panic hook and Ctrl-C watcher restore the terminal first, then report or exit as they normally would
+    pub fn new() -> Self {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+
+        if let Ok(mut signals) = Signals::new([SIGINT]) {
+            thread::spawn(move || {
+                if signals.forever().next().is_some() {
+                    restore_terminal();
+                    std::process::exit(130);
+                }
+            });
+        }
+
+        TerminalGuard { restore: restore_terminal }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        (self.restore)();
+    }
+}
+
+// the draw functions only ever queue onto the stdout they're given, never print!, so a frame goes out in one
+// piece when it is flushed
+pub fn draw_once(stdout: &mut impl Write, values: &DrawValues, render: &mut RenderState) {
+
+    let score_name: Vec<String> = values
+        .categories
+        .iter()
+        .enumerate()
+        .map(|(i, score_type)| format!("{:<2} - {}", i + 1, score_type.name()))
+        .collect();
+
+    stdout.execute(terminal::Clear(terminal::ClearType::All)).unwrap();
+    // nothing draw_update put down survives the clear
+    *render = RenderState::default();
+
+    //border
+    for y in 0..GAME_HEIGHT {
+        for x in 0..GAME_WIDTH {
+        if (y == 0 || y == GAME_HEIGHT - 1) || (x == 0 || x == GAME_WIDTH - 1) {
+            // in this loop we are more efficient by not flushing the buffer.
+            stdout
+            .queue(cursor::MoveTo(x,y)).unwrap()
+            .queue(style::PrintStyledContent( "▓".with(values.theme.border))).unwrap();
+        }
+        }
+    }
+
+    stdout.queue(cursor::MoveTo(values.title_pos.0, values.title_pos.1)).unwrap();
+    stdout.queue(style::PrintStyledContent(" YACHT DICE "
+            .with(values.theme.title.0)
+            .on(values.theme.title.1)
+            .attribute(Attribute::Bold)
+        )).unwrap();
+
+    //DRAW SCORE TABLE
+    let top_corner = values.score_table_corner;
+
+    stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 - 1)).unwrap();
+    stdout.queue(style::Print("╔═SCORE TABLE══════════╤═══╗")).unwrap();
+
+    let rows = values.categories.len() as u16;
+
+    for i in 0..rows {
+        stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + (i*2))).unwrap();
+        stdout.queue(style::Print(format!("║ {}", score_name[i as usize]))).unwrap();
+
+        stdout.queue(cursor::MoveTo(top_corner.0 + 24, top_corner.1 + (i*2))).unwrap();
+        stdout.queue(style::Print("   ║")).unwrap();
+
+        stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + (i*2) + 1)).unwrap();
+
+        if i != rows - 1 {
+            stdout.queue(style::Print("╟━━━━━━━━━━━━━━━━━━━━━━╋━━━╢")).unwrap();
+        } else {
+            stdout.queue(style::Print("╟━━━━━━━━━━━━━━━━━━━━┯━┻━━━╢")).unwrap();
+        }
+        
+    }
+
+    stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + rows * 2)).unwrap();
+
+    stdout.queue(style::Print("║ TOTAL              │     ║")).unwrap();
+
+    stdout.queue(cursor::MoveTo(top_corner.0, top_corner.1 + rows * 2 + 1)).unwrap();
+
+    stdout.queue(style::Print("╚════════════════════╧═════╝")).unwrap();
+
+    //shortcut legend, in the gap between the score table and the game status
+
+    let legend = values.legend_pos;
+
+    stdout.queue(cursor::MoveTo(legend.0, legend.1)).unwrap();
+    stdout.queue(style::Print("┌─SHORTCUTS───────────┐")).unwrap();
+
+    for (i, line) in values.legend.iter().enumerate() {
+        stdout.queue(cursor::MoveTo(legend.0, legend.1 + 1 + i as u16)).unwrap();
+        stdout.queue(style::Print(format!("│ {:<19} │", line))).unwrap();
+    }
+
+    stdout.queue(cursor::MoveTo(legend.0, legend.1 + 1 + values.legend.len() as u16)).unwrap();
+    stdout.queue(style::Print("└─────────────────────┘")).unwrap();
+
+    //dice frames are drawn with the faces in draw_update as their colour follows the holds
+
+    stdout.flush().unwrap();
+
+}
+
+// what draw_update last put on screen, so each frame only queues the cells that changed. Anything that
+// clears the screen starts it over, which draw_once does
+#[derive(Default)]
+pub struct RenderState {
+    cells: HashMap<(u16, u16), String>,
+    dice: Vec<(u8, bool, Color)>,
+}
+
+impl RenderState {
+    // text is queued at pos unless it is already what was last drawn there. Styled text goes in already
+    // formatted, so a change of colour counts as a change
+    fn put(&mut self, stdout: &mut impl Write, pos: (u16, u16), text: String) {
+        if self.cells.get(&pos) == Some(&text) {
+            return;
+        }
+        stdout.queue(cursor::MoveTo(pos.0, pos.1)).unwrap();
+        stdout.queue(style::Print(&text)).unwrap();
+        self.cells.insert(pos, text);
+    }
+}
+
+pub fn draw_update(game: &Game, stdout: &mut impl Write, values: &DrawValues, render: &mut RenderState) {
+
+    let mut score_status: Vec<String> = Vec::new();
+
+    for score_type in &values.categories {
+        score_status.push(game.player().score_table.get_table_value(score_type));
+    }
+
+    //Draw Table Values
+
+    let top_corner = values.score_table_corner;
+
+    // with several players the header says whose card is showing
+    if game.is_multiplayer() {
+        let title: String = format!("{}'S CARD", game.player().name.to_uppercase()).chars().take(20).collect();
+        render.put(stdout, (top_corner.0, top_corner.1 - 1), format!("╔═{}{}╤═══╗", title, "═".repeat(21 - title.chars().count())));
+    }
+
+    for (i, status) in score_status.iter().enumerate() {
+        let i = i as u16;
+
+        render.put(stdout, (top_corner.0 + 23, top_corner.1 + (i*2)), format!("┃{}", status));
+    }
+
+    // while hints are full, the open category these dice score most in gets a star beside its name
+    let best = if game.game_state == GameStates::Rolling && game.drill.is_none() && game.hint_level() == HintLevel::Full {
+        game.player().score_table.best_open_score(&game.current_roll, &game.rules).map(|(score_type, _)| score_type)
+    } else {
+        None
+    };
+    for (i, score_type) in values.categories.iter().enumerate() {
+        let star = if best == Some(*score_type) {
+            "*".with(values.theme.preview).attribute(Attribute::Bold).to_string()
+        } else {
+            " ".to_string()
+        };
+        render.put(stdout, (top_corner.0 + 22, top_corner.1 + (i as u16 * 2)), star);
+    }
+
+    // previewed scores sit just outside the table, blanked again when not previewing
+    let preview = if game.preview { game.preview_scores() } else { HashMap::new() };
+    for (i, score_type) in values.categories.iter().enumerate() {
+        let text = match preview.get(score_type) {
+            Some(score) => format!("{:>3}", score).with(values.theme.preview).to_string(),
+            None => "   ".to_string(),
+        };
+        render.put(stdout, (top_corner.0 + 28, top_corner.1 + (i as u16 * 2)), text);
+    }
+
+    render.put(stdout, (top_corner.0 + 23, top_corner.1 + values.categories.len() as u16 * 2), format!("{}  ", game.total()));
+
+    //status line, centred under the title
+
+    let status = game.status_line();
+    let left = (GAME_WIDTH as usize - status.chars().count()) / 2 - 1;
+    render.put(stdout, (1, values.title_pos.1 + 1), format!("{}{:<width$}", " ".repeat(left), status, width = GAME_WIDTH as usize - 2 - left));
+
+    //DRAW GAME STATE
+    // the last roll is always Final however many a turn has, the ones in between are counted off
+    let (banner, colors) = match game.game_state {
+        GameStates::Rolling if game.is_final_roll() => (" Final Roll ".to_string(), values.theme.final_roll),
+        GameStates::Rolling if game.rolls_used == 1 => (" First Roll ".to_string(), values.theme.first_roll),
+        GameStates::Rolling => (format!(" {} Roll ", middle_roll_word(game.rolls_used)), values.theme.middle_roll),
+        GameStates::GameOver => (" GAME OVER ".to_string(), values.theme.game_over),
+    };
+    // the banner is padded out to where the longest one ends so a shorter one leaves nothing behind
+    let padding = " ".repeat(15_usize.saturating_sub(banner.chars().count()));
+    let banner = banner.with(colors.0).on(colors.1).attribute(Attribute::Bold);
+    render.put(stdout, values.game_status_pos, format!("Game Status:{}{}", banner, padding));
+
+    let score_table = &game.player().score_table;
+    render.put(stdout, (values.game_status_pos.0, values.game_status_pos.1 + 4), format!("Upper: {}/{}  Bonus: {}   ", score_table.upper_total(), UPPER_BONUS_THRESHOLD, score_table.upper_bonus()));
+    render.put(stdout, (values.game_status_pos.0, values.game_status_pos.1 + 5), format!("Yacht Bonus: {}   ", score_table.yacht_bonus));
+
+    // everyone's totals, whoever's turn it is picked out. There's room for three before the dice
+    if game.is_multiplayer() {
+        for (i, player) in game.players.iter().enumerate().take(3) {
+            let name: String = player.name.chars().take(16).collect();
+            let line = format!(" {:<16}{:>4} ", name, player.total());
+            let line = if i == game.current_player && game.game_state == GameStates::Rolling {
+                line.with(values.theme.current_player.0).on(values.theme.current_player.1).to_string()
+            } else {
+                line
+            };
+            render.put(stdout, (values.game_status_pos.0, values.game_status_pos.1 + 6 + i as u16), line);
+        }
+        if game.players.len() > 3 {
+            render.put(stdout, (values.game_status_pos.0 + 23, values.game_status_pos.1 + 8), format!("+{} more", game.players.len() - 3));
+        }
+    }
+
+    draw_timer(game, stdout, values);
+
+    if let Some(face) = game.lucky_face {
+        render.put(stdout, (values.game_status_pos.0, values.game_status_pos.1 + 2), format!("Lucky Face: {}  Bonus: {}   ", face, game.player().lucky_bonus));
+    }
+    
+    let dice_corner = values.dice_corner;
+    //draw faces
+
+    // a sixth die only fits with the dice a little closer together
+    let spacing = if game.current_roll.dice.len() > 5 { 10 } else { 11 };
+    let order = display_order(&game.current_roll.dice, values.sorted_view);
+    // a die is redrawn whole, frame and hold marker too, when its face, hold or colour changes
+    let mut dice = Vec::new();
+    for &i in &order {
+        let held = game.current_roll.holds[i];
+        let wanted = game.highlight.is_some_and(|score_type| game.current_roll.suggested_holds(&score_type)[i]);
+        let color = if wanted { values.dice_colors.wanted } else { values.dice_colors.for_hold(held) };
+        dice.push((game.current_roll.dice[i], held, color));
+    }
+    for (slot, &(face, held, color)) in dice.iter().enumerate() {
+        let center = (dice_corner.0 + 4 + spacing * slot as u16, dice_corner.1 + 2);
+
+        // the number 'hold' and the rest take, which a sorted view no longer shows by position
+        if values.sorted_view {
+            render.put(stdout, (center.0, dice_corner.1 + 7), (order[slot] + 1).to_string());
+        }
+
+        if render.dice.get(slot) == Some(&(face, held, color)) {
+            continue;
+        }
+        match values.dice_style {
+            DiceStyle::Pips => draw_dice_at(stdout, center, face, held, color, values.pip),
+            DiceStyle::Numbers => draw_dice_number_at(stdout, center, face, held, color),
+            DiceStyle::Unicode => draw_dice_unicode(stdout, center, face, held, color),
+        }
+
+        //draw holds
+        stdout.queue(cursor::MoveTo(center.0 - 1, dice_corner.1 + 6)).unwrap();
+        stdout.queue(style::Print(if held { "[X]" } else { "[ ]" })).unwrap();
+    }
+    render.dice = dice;
+
+    //draw info panel
+
+    let panel = values.panel_pos;
+
+    for i in 0..PANEL_HEIGHT {
+        let line = game.panel.get(i as usize).map_or("", |line| line.as_str());
+        render.put(stdout, (panel.0, panel.1 + i), format!("{:<width$}", line, width = PANEL_WIDTH as usize));
+    }
+
+    //footer, the message above the prompt with the rolls left tucked in at the end of the prompt line
+
+    let prompt = values.prompt_pos;
+
+    // the numbers of the categories still open sit under the info panel, just above the message
+    let open: Vec<String> = game
+        .player()
+        .score_table
+        .open_categories(&game.rules)
+        .iter()
+        .map(|score_type| (values.categories.iter().position(|other| other == score_type).unwrap_or(0) + 1).to_string())
+        .collect();
+    render.put(stdout, (values.panel_pos.0, prompt.1 - 1), format!("{:<width$}", format!("Open: {}", open.join(" ")), width = PANEL_WIDTH as usize));
+
+    // the message sits between the left margin and a matching one on the right, after the 4 wide "--] " marker
+    let msg_width = (GAME_WIDTH - prompt.0 * 2 - 4) as usize;
+    let msg_lines = wrap_message(&game.msg, msg_width, MSG_LINES as usize);
+
+    for i in 0..MSG_LINES {
+        let text = match msg_lines.get(i as usize) {
+            Some(line) => {
+                let marker = if i == 0 { "--] " } else { "    " };
+                let padded = format!("{:<width$}", format!("{}{}", marker, line), width = msg_width + 4);
+                if game.msg_error { padded.with(values.theme.error).to_string() } else { padded }
+            }
+            None => " ".repeat(msg_width + 4),
+        };
+        render.put(stdout, (prompt.0, prompt.1 + i), text);
+    }
+
+    let indicator = match game.game_state {
+        GameStates::GameOver => "game over".to_string(),
+        GameStates::Rolling => format!("rolls left {}", game.rolls_left()),
+    };
+
+    // the prompt line is always redrawn, it is what clears away the last command typed
+    stdout.queue(cursor::MoveTo(prompt.0, prompt.1 + MSG_LINES)).unwrap();
+    stdout.queue(style::Print(format!("-->{:>width$}", indicator, width = msg_width + 1))).unwrap();
+    stdout.queue(cursor::MoveTo(prompt.0 + 4, prompt.1 + MSG_LINES)).unwrap();
+
+    stdout.flush().unwrap();
+}
+
+fn middle_roll_word(rolls_used: u8) -> String {
+    match rolls_used {
+        2 => "Second".to_string(),
+        3 => "Third".to_string(),
+        4 => "Fourth".to_string(),
+        5 => "Fifth".to_string(),
+        n => format!("#{}", n),
+    }
+}
+
+// the seconds left in the turn under the game status, redrawn on its own while waiting for input so the cursor
+// is put back wherever the player is typing
+pub fn draw_timer(game: &Game, stdout: &mut impl Write, values: &DrawValues) {
+    if game.turn_limit.is_none() {
+        return;
+    }
+
+    let text = match game.time_left() {
+        Some(left) => format!("Time left: {}s", left.as_secs_f32().ceil() as u64),
+        None => "".to_string(),
+    };
+
+    stdout.queue(cursor::SavePosition).unwrap();
+    stdout.queue(cursor::MoveTo(values.game_status_pos.0, values.game_status_pos.1 + 1)).unwrap();
+    stdout.queue(style::Print(format!("{:<20}", text))).unwrap();
+    stdout.queue(cursor::RestorePosition).unwrap();
+    stdout.flush().unwrap();
+}
+
+// the commands most turns need, each by its shortest word, for the legend beside the board. The words are
+// looked up in COMMANDS so the legend can't drift from what the parser takes
+pub fn legend_lines() -> Vec<String> {
+    [("roll", "", "roll"), ("sort", "", "sort dice"), ("hold", " N", "hold die N"), ("score", " N", "score type N"), ("new", "", "new game"), ("quit", "", "quit")]
+        .iter()
+        .filter_map(|&(name, arg, what)| find_command(name).map(|info| (info.aliases.first().unwrap_or(&info.name), arg, what)))
+        .map(|(word, arg, what)| format!("{:<7}{}", format!("{}{}", word, arg), what))
+        .collect()
+}
+
+pub fn commands_panel() -> Vec<String> {
+    let entries: Vec<String> = COMMANDS
+        .iter()
+        .filter(|info| !info.hidden)
+        .map(|info| {
+            if info.aliases.is_empty() {
+                info.usage.to_string()
+            } else {
+                format!("{} ({})", info.usage, info.aliases.join(", "))
+            }
+        })
+        .collect();
+
+    let mut lines = vec!["COMMANDS   (shortcuts in brackets)".to_string()];
+
+    let rows = entries.len().div_ceil(2);
+    for row in 0..rows {
+        let mut line = String::new();
+        for entry in entries.iter().skip(row).step_by(rows) {
+            line.push_str(&format!("{:<28}", entry));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines
+}
+
+pub fn saves_panel(dir: &str) -> Vec<String> {
+    let mut lines = vec!["SAVED GAMES".to_string()];
+
+    let slots = saved_slots(dir);
+    if slots.is_empty() {
+        lines.push("No saves yet, try 'save <name>'".to_string());
+    }
+    for (name, note) in slots {
+        lines.push(format!("{:<16}{}", name, note).chars().take(PANEL_WIDTH as usize).collect());
+    }
+
+    lines
+}
+
+// two columns of five so the top ten fit the panel
+pub fn leaderboard_panel() -> Vec<String> {
+    let entries = match load_leaderboard(LEADERBOARD_PATH) {
+        Ok(entries) => top_scores(entries, LEADERBOARD_SIZE),
+        Err(e) => return vec!["LEADERBOARD".to_string(), e],
+    };
+
+    let mut lines = vec!["LEADERBOARD".to_string()];
+    if entries.is_empty() {
+        lines.push("No scores recorded yet".to_string());
+    }
+
+    let rows = entries.len().div_ceil(2);
+    for row in 0..rows {
+        let mut line = String::new();
+        for (i, (name, score)) in entries.iter().enumerate().skip(row).step_by(rows) {
+            let name: String = name.chars().take(14).collect();
+            line.push_str(&format!("{:>2}. {:<14}{:>4}     ", i + 1, name, score));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines
+}
+
+// word wraps a message to at most max_lines lines of width characters, splitting words too long to fit
+fn wrap_message(msg: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+
+    for word in msg.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+
+        while !word.is_empty() {
+            let line_len = line.chars().count();
+            let needed = if line.is_empty() { word.len() } else { line_len + 1 + word.len() };
+
+            if needed <= width {
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.extend(word.drain(..));
+            } else if line.is_empty() {
+                line.extend(word.drain(..width));
+                lines.push(std::mem::take(&mut line));
+            } else {
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines.truncate(max_lines);
+    lines
+}
+
+fn draw_dice_frame(stdout: &mut impl Write, dice_center: (u16, u16), held: bool, color: Color) {
+    //labelled when the die is held
+    let top = if held { "┌─HELD──┐" } else { "┌───────┐" };
+    stdout.queue(cursor::MoveTo(dice_center.0 - 4, dice_center.1 - 2)).unwrap();
+    stdout.queue(style::PrintStyledContent(top.with(color))).unwrap();
+    for y in 0..3 {
+        stdout.queue(cursor::MoveTo(dice_center.0 - 4, dice_center.1 - 1 + y)).unwrap();
+        stdout.queue(style::PrintStyledContent("│       │".with(color))).unwrap();
+    }
+    stdout.queue(cursor::MoveTo(dice_center.0 - 4, dice_center.1 + 2)).unwrap();
+    stdout.queue(style::PrintStyledContent("└───────┘".with(color))).unwrap();
+}
+
+fn numeric_face(num: u8) -> String {
+    format!("[{}]", num)
+}
+
+// the plain alternative to pips, the value written in the middle of the frame
+fn draw_dice_number_at(stdout: &mut impl Write, dice_center: (u16, u16), num: u8, held: bool, color: Color) {
+    draw_dice_frame(stdout, dice_center, held, color);
+
+    stdout.queue(cursor::MoveTo(dice_center.0 - 1, dice_center.1)).unwrap();
+    stdout.queue(style::PrintStyledContent(numeric_face(num).with(color))).unwrap();
+}
+
+// ⚀ to ⚅, None for anything that isn't a die face
+fn unicode_face(num: u8) -> Option<char> {
+    if !(1..=6).contains(&num) {
+        return None;
+    }
+    char::from_u32(0x2680 + num as u32 - 1)
+}
+
+// the terminal's locale settings, the first one set wins like it does for the C library
+pub fn utf8_locale() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|value| value.to_uppercase().replace('-', "").contains("UTF8"))
+}
+
+fn draw_dice_unicode(stdout: &mut impl Write, dice_center: (u16, u16), num: u8, held: bool, color: Color) {
+    draw_dice_frame(stdout, dice_center, held, color);
+
+    stdout.queue(cursor::MoveTo(dice_center.0, dice_center.1)).unwrap();
+    stdout.queue(style::PrintStyledContent(unicode_face(num).unwrap_or('?').with(color))).unwrap();
+}
+
+fn draw_dice_at(stdout: &mut impl Write, dice_center: (u16, u16), num: u8, held: bool, color: Color, pip: char) {
+    draw_dice_frame(stdout, dice_center, held, color);
+
+    stdout.queue(cursor::MoveTo(dice_center.0, dice_center.1)).unwrap();
+
+    let dot_symbol = pip.with(color);
+    
+    match num {
+        1 => {
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+        },
+        2 => {
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+        },
+        3 => {
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+        },
+        4 => {
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+
+        },
+        5 => {
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+
+        },
+        6 => {
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1 - 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1 + 1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 - 2, dice_center.1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+            stdout.queue(cursor::MoveTo(dice_center.0 + 2, dice_center.1)).unwrap();
+            stdout.queue(style::PrintStyledContent(dot_symbol)).unwrap();
+        },
+        // not a face pips can show, from_dice doesn't check what it's given
+        _ => {
+            stdout.queue(style::PrintStyledContent('?'.with(color))).unwrap();
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, sync::atomic::{AtomicUsize, Ordering}};
+
+    use super::*;
+    use crate::{game::{Command, GameOptions}, roll::{DiceNum, Roll}, score::ScoreType};
+
+    // renders the whole board for the game the way the first frame of the terminal loop does
+    fn render(game: &Game) -> Vec<u8> {
+        let values = DrawValues::default();
+        let mut output = Vec::new();
+        let mut render = RenderState::default();
+        draw_once(&mut output, &values, &mut render);
+        draw_update(game, &mut output, &values, &mut render);
+        output
+    }
+
+    // compares against tests/golden/<name>, or writes it there instead when UPDATE_GOLDEN is set
+    fn check_golden(name: &str, output: &[u8]) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name);
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, output).unwrap();
+            return;
+        }
+        let expected = fs::read(&path).unwrap_or_else(|e| panic!("Couldn't read {}: {}, run with UPDATE_GOLDEN=1 to write it", path.display(), e));
+        assert!(output == expected, "the board no longer matches {}, run with UPDATE_GOLDEN=1 if the change is intended", path.display());
+    }
+
+    fn play(game: &mut Game, commands: &[Command]) {
+        for command in commands {
+            game.msg = game.attempt_command(command).unwrap_or_else(|e| e.to_string());
+        }
+    }
+
+    #[test]
+    fn dice_colour_follows_the_hold() {
+        let colors = DiceColors { free: Color::White, held: Color::Cyan, wanted: Color::Yellow };
+        assert_eq!(colors.for_hold(false), Color::White);
+        assert_eq!(colors.for_hold(true), Color::Cyan);
+
+        assert_eq!(parse_color("gray"), Some(Color::Grey));
+        assert_eq!(parse_color("magenta"), Some(Color::Magenta));
+        assert_eq!(parse_color("purple"), None);
+    }
+
+    #[test]
+    fn dice_drawn_as_numbers() {
+        let layout = DrawValues { dice_style: DiceStyle::Numbers, ..DrawValues::default() };
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![3, 5, 5, 1, 6], 6).unwrap();
+        game.current_roll.toggle_hold(&DiceNum::Second);
+
+        let mut output = Vec::new();
+        draw_update(&game, &mut output, &layout, &mut RenderState::default());
+        let output = String::from_utf8(output).unwrap();
+
+        let faces: Vec<&str> = output.match_indices('[').filter_map(|(i, _)| output.get(i..i + 3)).filter(|face| face.as_bytes()[1].is_ascii_digit() && face.ends_with(']')).collect();
+        assert_eq!(faces, ["[3]", "[5]", "[5]", "[1]", "[6]"]);
+        // the held frame works the same as with pips
+        assert_eq!(output.matches("HELD").count(), 1);
+    }
+
+    #[test]
+    fn footer_for_a_known_state() {
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        play(&mut game, &[Command::Roll, Command::Score(ScoreType::Chance), Command::Roll]);
+        game.msg = "word ".repeat(20).trim_end().to_string();
+        let output = String::from_utf8(render(&game)).unwrap();
+
+        // rows and columns count from 1 in the escape codes, the message wraps onto its second line at 85 wide
+        let first = format!("{} word", "word ".repeat(16).trim_end());
+        assert!(output.contains(&format!("\x1b[32;4H--] {}", first)));
+        assert!(output.contains(&format!("\x1b[33;4H    {}", "word word word")));
+        // the rolls left end the prompt line and the cursor goes back to just after the arrow
+        assert!(output.ends_with(&format!("\x1b[34;4H-->{:>86}\x1b[34;8H", "rolls left 1")));
+    }
+
+    #[test]
+    fn guard_restores_the_terminal_when_dropped() {
+        static RESTORED: AtomicUsize = AtomicUsize::new(0);
+        fn restore() {
+            RESTORED.fetch_add(1, Ordering::SeqCst);
+        }
+
+        {
+            let _guard = TerminalGuard { restore };
+            assert_eq!(RESTORED.load(Ordering::SeqCst), 0);
+        }
+        assert_eq!(RESTORED.load(Ordering::SeqCst), 1);
+
+        let mut output = Vec::new();
+        reset_screen(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "\x1b[0m\x1b[2J\x1b[1;1H\x1b[?25h");
+    }
+
+    #[test]
+    fn board_needs_95_by_35() {
+        assert!(terminal_fits((95, 35)));
+        assert!(terminal_fits((200, 60)));
+        assert!(!terminal_fits((94, 35)));
+        assert!(!terminal_fits((95, 34)));
+        assert!(!terminal_fits((80, 24)));
+
+        let mut output = Vec::new();
+        draw_too_small(&mut output, (80, 24));
+        assert!(String::from_utf8(output).unwrap().contains("Please enlarge your terminal to 95x35 (it's 80x24 now)"));
+    }
+
+    #[test]
+    fn held_dice_are_drawn_in_the_held_colour() {
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![2, 2, 2, 2, 2], 6).unwrap();
+        game.current_roll.toggle_hold(&DiceNum::Fourth);
+        let output = String::from_utf8(render(&game)).unwrap();
+
+        // one frame top in green for the held die, white for the other four
+        assert_eq!(output.matches(&"┌─HELD──┐".with(Color::Green).to_string()).count(), 1);
+        assert_eq!(output.matches(&"┌───────┐".with(Color::White).to_string()).count(), 4);
+        assert!(!output.contains(&"┌─HELD──┐".with(Color::White).to_string()));
+    }
+
+    #[test]
+    fn faces_map_to_die_glyphs() {
+        let faces: String = (1..=6).filter_map(unicode_face).collect();
+        assert_eq!(faces, "⚀⚁⚂⚃⚄⚅");
+        assert_eq!(unicode_face(1), Some('\u{2680}'));
+        assert_eq!(unicode_face(0), None);
+        assert_eq!(unicode_face(7), None);
+    }
+
+    #[test]
+    fn themes_by_name() {
+        let contrast = Theme::from_name("contrast").unwrap();
+        assert_eq!(contrast.final_roll, (Color::Black, Color::Red));
+        assert_eq!(contrast.game_over, (Color::Black, Color::Cyan));
+        assert_eq!(Theme::from_name("mono").unwrap().preview, Color::Grey);
+        for name in THEME_NAMES {
+            assert!(Theme::from_name(name).is_ok(), "{}", name);
+        }
+
+        assert_eq!(Theme::from_name("neon"), Err("Unknown theme 'neon', try classic, contrast, mono".to_string()));
+    }
+
+    #[test]
+    fn names_status_and_holds_go_to_the_writer() {
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll.toggle_hold(&DiceNum::from_number(2, 5).unwrap());
+        let output = String::from_utf8(render(&game)).unwrap();
+
+        // anything print!ed would miss the buffer, so every one of these has to be in it
+        for (i, score_type) in game.rules.categories().iter().enumerate() {
+            assert!(output.contains(&format!("║ {:<2} - {}", i + 1, score_type.name())), "{}", score_type.name());
+        }
+        assert_eq!(output.matches('┃').count(), game.rules.categories().len());
+        assert_eq!(output.matches("[X]").count(), 1);
+        assert_eq!(output.matches("[ ]").count(), 4);
+    }
+
+    #[test]
+    fn roll_banner_counts_up_to_the_final_roll() {
+        let mut game = Game::new(&GameOptions { seed: Some(1), rolls_per_turn: 4, ..GameOptions::default() });
+        let mut banners = Vec::new();
+        for _ in 0..4 {
+            let output = String::from_utf8(render(&game)).unwrap();
+            let banner = ["First", "Second", "Third", "Final"].into_iter().find(|word| output.contains(&format!(" {} Roll ", word)));
+            banners.push(banner);
+            play(&mut game, &[Command::Roll]);
+        }
+        assert_eq!(banners, [Some("First"), Some("Second"), Some("Third"), Some("Final")]);
+    }
+
+    #[test]
+    fn only_changed_cells_are_queued() {
+        let mut render = RenderState::default();
+        let mut put = |text: &str| {
+            let mut output = Vec::new();
+            render.put(&mut output, (3, 4), text.to_string());
+            output
+        };
+        assert!(!put("Upper: 12").is_empty());
+        assert!(put("Upper: 12").is_empty());
+        assert!(!put("Upper: 15").is_empty());
+
+        // a second frame of the same game only draws the prompt line, which is always redrawn, and after a hold
+        // only the die that changed joins it
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        let values = DrawValues::default();
+        let mut render = RenderState::default();
+        let mut output = Vec::new();
+        draw_once(&mut output, &values, &mut render);
+        draw_update(&game, &mut output, &values, &mut render);
+
+        let mut output = Vec::new();
+        draw_update(&game, &mut output, &values, &mut render);
+        let prompt_only = String::from_utf8(output).unwrap();
+        assert!(prompt_only.starts_with("\x1b[34;4H-->"), "{:?}", prompt_only);
+        assert_eq!(prompt_only.matches('\x1b').count(), 2);
+
+        game.current_roll.toggle_hold(&DiceNum::from_number(3, 5).unwrap());
+        let mut output = Vec::new();
+        draw_update(&game, &mut output, &values, &mut render);
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("HELD").count(), 1);
+        assert!(output.ends_with(&prompt_only));
+        assert!(!output.contains("Upper:"));
+    }
+
+    #[test]
+    fn pips_drawn_with_the_chosen_symbol() {
+        let layout = DrawValues { pip: '#', ..DrawValues::default() };
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![1, 2, 3, 4, 6], 6).unwrap();
+
+        let mut output = Vec::new();
+        draw_update(&game, &mut output, &layout, &mut RenderState::default());
+        let output = String::from_utf8(output).unwrap();
+
+        // a pip for every point on the dice, and none of the default ones
+        assert_eq!(output.matches(&'#'.with(Color::White).to_string()).count(), 16);
+        assert!(!output.contains(&'O'.with(Color::White).to_string()));
+    }
+
+    #[test]
+    fn faces_pips_cant_show_are_drawn_as_a_question_mark() {
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::from_dice(vec![0, 1, 1, 1, 0], 6);
+
+        let mut output = Vec::new();
+        draw_update(&game, &mut output, &DrawValues::default(), &mut RenderState::default());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches(&'?'.with(Color::White).to_string()).count(), 2);
+        assert_eq!(output.matches(&'O'.with(Color::White).to_string()).count(), 3);
+    }
+
+    #[test]
+    fn wanted_dice_are_drawn_in_the_wanted_colour() {
+        let layout = DrawValues::default();
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![6, 2, 6, 4, 6], 6).unwrap();
+        game.highlight = Some(ScoreType::Sixes);
+
+        let mut output = Vec::new();
+        draw_update(&game, &mut output, &layout, &mut RenderState::default());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches(&"┌───────┐".with(layout.dice_colors.wanted).to_string()).count(), 3);
+        assert_eq!(output.matches(&"┌───────┐".with(Color::White).to_string()).count(), 2);
+    }
+
+    #[test]
+    fn sorted_view_orders_the_dice_on_screen_only() {
+        let dice = [5, 2, 6, 2, 1];
+        let order = display_order(&dice, true);
+        assert_eq!(order, [4, 1, 3, 0, 2]);
+        assert_eq!(order.iter().map(|&i| dice[i]).collect::<Vec<u8>>(), [1, 2, 2, 5, 6]);
+        assert_eq!(display_order(&dice, false), [0, 1, 2, 3, 4]);
+
+        // the faces go on screen in that order, the held die included
+        let layout = DrawValues { sorted_view: true, dice_style: DiceStyle::Numbers, ..DrawValues::default() };
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(dice.to_vec(), 6).unwrap();
+        game.current_roll.toggle_hold(&DiceNum::from_number(1, 5).unwrap());
+
+        let mut output = Vec::new();
+        draw_update(&game, &mut output, &layout, &mut RenderState::default());
+        let output = String::from_utf8(output).unwrap();
+        let faces: Vec<&str> = output.match_indices('[').filter_map(|(i, _)| output.get(i..i + 3)).filter(|face| face.as_bytes()[1].is_ascii_digit() && face.ends_with(']')).collect();
+        assert_eq!(faces, ["[1]", "[2]", "[2]", "[5]", "[6]"]);
+        assert_eq!(output.matches("HELD").count(), 1);
+    }
+
+    #[test]
+    fn long_messages_wrap_at_word_boundaries() {
+        assert_eq!(wrap_message("the quick brown fox jumps over the lazy dog", 15, 5), vec!["the quick brown", "fox jumps over", "the lazy dog"]);
+        assert_eq!(wrap_message("short", 15, 2), vec!["short"]);
+        assert_eq!(wrap_message("", 15, 2), Vec::<String>::new());
+    }
+
+    #[test]
+    fn wrapping_splits_long_words_and_stops_at_max_lines() {
+        assert_eq!(wrap_message("abcdefghij xy", 4, 5), vec!["abcd", "efgh", "ij", "xy"]);
+        assert_eq!(wrap_message("one two three four five", 8, 2), vec!["one two", "three"]);
+    }
+
+    #[test]
+    fn initial_board() {
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![3, 6, 3, 5, 2], 6).unwrap();
+        check_golden("initial_board.ansi", &render(&game));
+    }
+
+    #[test]
+    fn mid_game_board() {
+        let mut game = Game::new(&GameOptions { seed: Some(1), ..GameOptions::default() });
+        game.current_roll = Roll::try_from_dice(vec![3, 6, 3, 5, 2], 6).unwrap();
+        play(&mut game, &[Command::Score(ScoreType::Chance)]);
+        game.current_roll = Roll::try_from_dice(vec![4, 2, 4, 1, 4], 6).unwrap();
+        play(&mut game, &[Command::Score(ScoreType::Fours)]);
+        game.current_roll = Roll::try_from_dice(vec![1, 5, 1, 6, 3], 6).unwrap();
+        play(&mut game, &[Command::HoldMany(vec![DiceNum::Second], vec![])]);
+        check_golden("mid_game_board.ansi", &render(&game));
+    }
+}
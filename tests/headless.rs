@@ -0,0 +1,53 @@
+use std::{io::Write, process::{Command, Stdio}};
+
+// runs the game with --headless, feeding it the script on stdin, and returns the report it prints
+fn play_headless(args: &[&str], script: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_yahtzee"))
+        .arg("--headless")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start the game");
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// the value on the report's line for the category, or the Total, None while the category is open
+fn card_value(report: &str, name: &str) -> Option<u16> {
+    report.lines().find_map(|line| {
+        let (label, value) = line.trim().rsplit_once(' ')?;
+        if label.trim() == name { value.parse().ok() } else { None }
+    })
+}
+
+#[test]
+fn scripted_game_reaches_its_total() {
+    // every turn rolls, keeps the first die, rolls again and scores the next category down the table
+    let script: String = (1..=13).map(|category| format!("roll\nhold 1\nroll\nscore {}\n", category)).collect();
+    let report = play_headless(&["--seed", "42"], &script);
+
+    assert!(report.contains("Game over"), "{}", report);
+    assert_eq!(card_value(&report, "Fours"), Some(4));
+    assert_eq!(card_value(&report, "Sixes"), Some(6));
+    assert_eq!(card_value(&report, "Chance"), Some(19));
+    assert_eq!(card_value(&report, "Total"), Some(29));
+}
+
+#[test]
+fn same_seed_same_game() {
+    let script: String = (1..=13).map(|category| format!("roll\nscore {}\n", category)).collect();
+    assert_eq!(play_headless(&["--seed", "7"], &script), play_headless(&["--seed", "7"], &script));
+}
+
+#[test]
+fn unfinished_script_leaves_the_game_open() {
+    let report = play_headless(&["--seed", "42"], "roll\nscore chance\nundo\n");
+
+    assert!(report.contains("Game not finished"), "{}", report);
+    assert_eq!(card_value(&report, "Chance"), None);
+    assert_eq!(card_value(&report, "Total"), Some(0));
+}